@@ -37,6 +37,7 @@ describe('ExportMnemonicButton Component', () => {
   const exportedMnemonicFromBackend = 'export winner thank wave sausage worth useful legal winner thank yellow test';
   const mockSavePath = '/fake/export-mnemonic.txt';
   const expectedEncodedData = new TextEncoder().encode(exportedMnemonicFromBackend);
+  const exportResultFromBackend = { mnemonic: exportedMnemonicFromBackend, passphrase_protected: false, warning: null };
 
   beforeEach(() => {
     vi.clearAllMocks();
@@ -95,7 +96,7 @@ describe('ExportMnemonicButton Component', () => {
 
   it('should handle successful export flow', async () => {
     vi.mocked(ask).mockResolvedValue(true); // User confirms dialog
-    vi.mocked(invoke).mockResolvedValue(exportedMnemonicFromBackend); // Backend returns mnemonic
+    vi.mocked(invoke).mockResolvedValue(exportResultFromBackend); // Backend returns mnemonic
     vi.mocked(mockAvailableFileSystem.pickSaveFile).mockResolvedValue(mockSavePath);
     vi.mocked(mockAvailableFileSystem.writeFile).mockResolvedValue(undefined); // Write succeeds
 
@@ -184,9 +185,62 @@ describe('ExportMnemonicButton Component', () => {
     expect(button).toBeEnabled();
   });
 
+  it('should confirm the passphrase warning before saving and note it in the file', async () => {
+    const warning = 'The mnemonic alone will not restore this wallet.';
+    vi.mocked(ask).mockResolvedValue(true);
+    vi.mocked(invoke).mockResolvedValue({ mnemonic: exportedMnemonicFromBackend, passphrase_protected: true, warning });
+    vi.mocked(mockAvailableFileSystem.pickSaveFile).mockResolvedValue(mockSavePath);
+    vi.mocked(mockAvailableFileSystem.writeFile).mockResolvedValue(undefined);
+
+    render(
+      <ExportMnemonicButton 
+        isDisabled={false} 
+        fileSystem={mockAvailableFileSystem} 
+        onSuccess={mockOnSuccess} 
+        onError={mockOnError} 
+      />
+    );
+    fireEvent.click(screen.getByRole('button'));
+
+    await waitFor(() => {
+      expect(ask).toHaveBeenCalledTimes(2);
+      expect(vi.mocked(ask).mock.calls[1][0]).toContain(warning);
+      expect(mockAvailableFileSystem.writeFile).toHaveBeenCalledWith(
+        mockSavePath,
+        new TextEncoder().encode(`${exportedMnemonicFromBackend}\n\n# ${warning}\n`),
+      );
+      expect(mockOnSuccess).toHaveBeenCalledWith(`Mnemonic exported successfully! ${warning}`);
+      expect(mockOnError).not.toHaveBeenCalled();
+    });
+  });
+
+  it('should not save when the passphrase warning is declined', async () => {
+    const warning = 'The mnemonic alone will not restore this wallet.';
+    vi.mocked(ask).mockResolvedValueOnce(true).mockResolvedValueOnce(false);
+    vi.mocked(invoke).mockResolvedValue({ mnemonic: exportedMnemonicFromBackend, passphrase_protected: true, warning });
+
+    render(
+      <ExportMnemonicButton 
+        isDisabled={false} 
+        fileSystem={mockAvailableFileSystem} 
+        onSuccess={mockOnSuccess} 
+        onError={mockOnError} 
+      />
+    );
+    fireEvent.click(screen.getByRole('button'));
+
+    await waitFor(() => {
+      expect(ask).toHaveBeenCalledTimes(2);
+      expect(mockAvailableFileSystem.pickSaveFile).not.toHaveBeenCalled();
+      expect(mockAvailableFileSystem.writeFile).not.toHaveBeenCalled();
+      expect(mockOnError).toHaveBeenCalledWith('Export cancelled by user.');
+      expect(mockOnSuccess).not.toHaveBeenCalled();
+    });
+  });
+
   it('should handle file save cancellation', async () => {
     vi.mocked(ask).mockResolvedValue(true);
-    vi.mocked(invoke).mockResolvedValue(exportedMnemonicFromBackend);
+    vi.mocked(invoke).mockResolvedValue(exportResultFromBackend);
     vi.mocked(mockAvailableFileSystem.pickSaveFile).mockResolvedValue(null); // Simulate cancellation
 
     render(
@@ -213,7 +267,7 @@ describe('ExportMnemonicButton Component', () => {
   it('should handle file write error', async () => {
     const writeError = new Error('Permission denied');
     vi.mocked(ask).mockResolvedValue(true);
-    vi.mocked(invoke).mockResolvedValue(exportedMnemonicFromBackend);
+    vi.mocked(invoke).mockResolvedValue(exportResultFromBackend);
     vi.mocked(mockAvailableFileSystem.pickSaveFile).mockResolvedValue(mockSavePath);
     vi.mocked(mockAvailableFileSystem.writeFile).mockRejectedValue(writeError);
 
@@ -12,6 +12,13 @@ interface ExportMnemonicButtonProps {
   onError: (message: string) => void;
 }
 
+// Shape returned by the `export_mnemonic` command
+interface MnemonicExport {
+  mnemonic: string;
+  passphrase_protected: boolean;
+  warning: string | null; // Set when the mnemonic alone won't restore the wallet
+}
+
 // Define potential status messages locally or import from parent constants
 const STATUS_EXPORT_SUCCESS = 'Mnemonic exported successfully!';
 const STATUS_EXPORT_CANCELLED_DIALOG = 'Export cancelled by user.';
@@ -19,6 +26,11 @@ const STATUS_EXPORT_CANCELLED_SAVE = 'File save cancelled.';
 const STATUS_EXPORT_ERROR = (msg: string) => `Export Error: ${msg}`;
 const STATUS_UNKNOWN_ERROR = 'An unknown error occurred.';
 
+// Builds the backup file. A passphrase warning goes in as a '#' note line after the phrase, which
+// ImportMnemonicButton skips when reading the file back.
+const buildExportFile = (exported: MnemonicExport): string =>
+  exported.warning ? `${exported.mnemonic}\n\n# ${exported.warning}\n` : exported.mnemonic;
+
 export const ExportMnemonicButton: React.FC<ExportMnemonicButtonProps> = ({
   isDisabled,
   fileSystem,
@@ -54,7 +66,21 @@ export const ExportMnemonicButton: React.FC<ExportMnemonicButtonProps> = ({
     // --- Proceed with Export ---
     setIsExportLoading(true);
     try {
-      const retrievedMnemonic = await invoke<string>('export_mnemonic');
+      const exported = await invoke<MnemonicExport>('export_mnemonic');
+
+      // The phrase alone won't restore a passphrase wallet: say so before anything is saved
+      if (exported.warning) {
+        const proceed = await ask(`${exported.warning} A note saying so will be added to the exported file.`, {
+          title: 'Passphrase Required to Restore',
+          okLabel: 'Save Anyway',
+          cancelLabel: 'Cancel',
+        });
+        if (!proceed) {
+          onError(STATUS_EXPORT_CANCELLED_DIALOG);
+          return;
+        }
+      }
+
       const savePath = await fileSystem.pickSaveFile({});
 
       if (!savePath) {
@@ -63,10 +89,10 @@ export const ExportMnemonicButton: React.FC<ExportMnemonicButtonProps> = ({
         return;
       }
 
-      const fileData = new TextEncoder().encode(retrievedMnemonic);
+      const fileData = new TextEncoder().encode(buildExportFile(exported));
       await fileSystem.writeFile(savePath, fileData);
 
-      onSuccess(STATUS_EXPORT_SUCCESS);
+      onSuccess(exported.warning ? `${STATUS_EXPORT_SUCCESS} ${exported.warning}` : STATUS_EXPORT_SUCCESS);
 
     } catch (error) {
       console.error("Export Error:", error);
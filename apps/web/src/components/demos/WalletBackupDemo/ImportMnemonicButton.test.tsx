@@ -112,7 +112,7 @@ describe('ImportMnemonicButton Component', () => {
     await waitFor(() => {
       expect(mockAvailableFileSystem.pickFile).toHaveBeenCalledTimes(1);
       expect(mockAvailableFileSystem.readFile).toHaveBeenCalledWith(mockFilePath);
-      expect(invoke).toHaveBeenCalledWith('import_mnemonic', { mnemonic: validMnemonic });
+      expect(invoke).toHaveBeenCalledWith('import_mnemonic', { mnemonic: validMnemonic, passphrase: '' });
       expect(invoke).toHaveBeenCalledTimes(1);
       // Verify onSuccess callback
       expect(mockOnSuccess).toHaveBeenCalledWith('Mnemonic imported successfully!', validMnemonic);
@@ -124,6 +124,44 @@ describe('ImportMnemonicButton Component', () => {
     expect(button).toHaveTextContent(/Import Mnemonic from File/i);
   });
 
+  it('should pass the entered passphrase to import_mnemonic', async () => {
+    vi.mocked(mockAvailableFileSystem.pickFile).mockResolvedValue([mockFilePath]);
+    vi.mocked(mockAvailableFileSystem.readFile).mockResolvedValue(fileContent);
+    vi.mocked(invoke).mockResolvedValue(undefined);
+
+    render(
+      <ImportMnemonicButton 
+        isDisabled={false} 
+        fileSystem={mockAvailableFileSystem} 
+        onSuccess={mockOnSuccess} 
+        onError={mockOnError} 
+      />
+    );
+    const passphraseInput = screen.getByLabelText(/Passphrase/i);
+    expect(passphraseInput).toHaveAttribute('type', 'password');
+    fireEvent.change(passphraseInput, { target: { value: 'TREZOR' } });
+    fireEvent.click(screen.getByRole('button'));
+
+    await waitFor(() => {
+      expect(invoke).toHaveBeenCalledWith('import_mnemonic', { mnemonic: validMnemonic, passphrase: 'TREZOR' });
+      expect(mockOnSuccess).toHaveBeenCalledWith('Mnemonic imported successfully!', validMnemonic);
+    });
+    // The passphrase isn't kept around after a successful import
+    expect(passphraseInput).toHaveValue('');
+  });
+
+  it('should disable the passphrase input when the button is disabled', () => {
+    render(
+      <ImportMnemonicButton 
+        isDisabled={true} 
+        fileSystem={mockAvailableFileSystem} 
+        onSuccess={mockOnSuccess} 
+        onError={mockOnError} 
+      />
+    );
+    expect(screen.getByTestId('import-passphrase-input')).toBeDisabled();
+  });
+
   it('should handle file pick cancellation', async () => {
     vi.mocked(mockAvailableFileSystem.pickFile).mockResolvedValue(null); // Simulate cancellation
 
@@ -173,6 +211,28 @@ describe('ImportMnemonicButton Component', () => {
     expect(button).toBeEnabled();
   });
 
+  it('should skip note lines written by the export', async () => {
+    const exportedFile = new TextEncoder().encode(`${validMnemonic}\n\n# You also need the passphrase.\n`);
+    vi.mocked(mockAvailableFileSystem.pickFile).mockResolvedValue([mockFilePath]);
+    vi.mocked(mockAvailableFileSystem.readFile).mockResolvedValue(exportedFile);
+    vi.mocked(invoke).mockResolvedValue(undefined);
+
+    render(
+      <ImportMnemonicButton 
+        isDisabled={false} 
+        fileSystem={mockAvailableFileSystem} 
+        onSuccess={mockOnSuccess} 
+        onError={mockOnError} 
+      />
+    );
+    fireEvent.click(screen.getByRole('button'));
+
+    await waitFor(() => {
+      expect(invoke).toHaveBeenCalledWith('import_mnemonic', { mnemonic: validMnemonic, passphrase: '' });
+      expect(mockOnSuccess).toHaveBeenCalledWith('Mnemonic imported successfully!', validMnemonic);
+    });
+  });
+
   it('should handle invalid mnemonic format (frontend validation)', async () => {
     const invalidFileContent = new TextEncoder().encode('too short');
     vi.mocked(mockAvailableFileSystem.pickFile).mockResolvedValue([mockFilePath]);
@@ -216,7 +276,7 @@ describe('ImportMnemonicButton Component', () => {
     fireEvent.click(button);
 
     await waitFor(() => {
-      expect(invoke).toHaveBeenCalledWith('import_mnemonic', { mnemonic: validMnemonic });
+      expect(invoke).toHaveBeenCalledWith('import_mnemonic', { mnemonic: validMnemonic, passphrase: '' });
       expect(mockOnError).toHaveBeenCalledWith(`Import Error: ${backendError}`);
       expect(mockOnSuccess).not.toHaveBeenCalled();
     });
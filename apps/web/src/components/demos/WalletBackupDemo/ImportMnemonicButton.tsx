@@ -1,6 +1,8 @@
 import React, { useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { Button } from '@/components/ui/button';
+import { Input } from '@/components/ui/input';
+import { Label } from '@/components/ui/label';
 import { FileUp } from 'lucide-react';
 import type { FileSystemCapabilities, CapabilityUnavailable } from '@paynless/types';
 
@@ -25,6 +27,8 @@ export const ImportMnemonicButton: React.FC<ImportMnemonicButtonProps> = ({
   onError,
 }) => {
   const [isImportLoading, setIsImportLoading] = useState<boolean>(false);
+  // Optional BIP-39 passphrase; an empty string means none (the backend treats both the same)
+  const [passphrase, setPassphrase] = useState<string>('');
 
   const handleActualImport = async () => {
     if (!fileSystem || !fileSystem.isAvailable || isDisabled || isImportLoading) return;
@@ -43,15 +47,22 @@ export const ImportMnemonicButton: React.FC<ImportMnemonicButtonProps> = ({
 
       // --- File Reading and Backend Invoke ---
       const fileContent = await fileSystem.readFile(selectedFilePath);
-      const importedMnemonic = new TextDecoder().decode(fileContent).trim();
+      // Lines starting with '#' are notes, e.g. the passphrase note ExportMnemonicButton writes
+      const importedMnemonic = new TextDecoder()
+        .decode(fileContent)
+        .split('\n')
+        .filter((line) => !line.trim().startsWith('#'))
+        .join(' ')
+        .trim();
 
       if (!importedMnemonic || importedMnemonic.split(/\s+/).length < 12) {
         throw new Error('Invalid mnemonic phrase format in file.');
       }
 
-      await invoke('import_mnemonic', { mnemonic: importedMnemonic });
+      await invoke('import_mnemonic', { mnemonic: importedMnemonic, passphrase });
 
-      // If invoke didn't throw, call onSuccess and pass the mnemonic up
+      // If invoke didn't throw, forget the passphrase, call onSuccess and pass the mnemonic up
+      setPassphrase('');
       onSuccess(STATUS_IMPORT_SUCCESS, importedMnemonic);
 
     } catch (error) {
@@ -67,15 +78,29 @@ export const ImportMnemonicButton: React.FC<ImportMnemonicButtonProps> = ({
     }
   };
 
+  const isUnavailable = isDisabled || isImportLoading || !fileSystem || !fileSystem.isAvailable;
+
   return (
-    <Button
-      onClick={handleActualImport}
-      disabled={isDisabled || isImportLoading || !fileSystem || !fileSystem.isAvailable}
-      data-testid="import-mnemonic-button"
-    >
-      <FileUp className="mr-2 h-4 w-4" />
-      {isImportLoading ? 'Importing...' : 'Import Mnemonic from File'}
-    </Button>
+    <div className="flex flex-col space-y-2">
+      <Label htmlFor="import-passphrase">Passphrase (optional)</Label>
+      <Input
+        id="import-passphrase"
+        type="password"
+        autoComplete="off"
+        value={passphrase}
+        onChange={(e) => setPassphrase(e.target.value)}
+        disabled={isUnavailable}
+        data-testid="import-passphrase-input"
+      />
+      <Button
+        onClick={handleActualImport}
+        disabled={isUnavailable}
+        data-testid="import-mnemonic-button"
+      >
+        <FileUp className="mr-2 h-4 w-4" />
+        {isImportLoading ? 'Importing...' : 'Import Mnemonic from File'}
+      </Button>
+    </div>
   );
 };
 
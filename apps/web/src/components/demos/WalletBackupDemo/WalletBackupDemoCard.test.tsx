@@ -200,7 +200,7 @@ describe('WalletBackupDemoCard Component', () => {
       const exportedMnemonicFromBackend = 'export different winner thank wave sausage worth useful legal winner thank yellow';
       const expectedEncodedData = new TextEncoder().encode(exportedMnemonicFromBackend);
       vi.mocked(ask).mockResolvedValue(true); // User confirms dialog
-      vi.mocked(invoke).mockResolvedValue(exportedMnemonicFromBackend); // Backend returns mnemonic
+      vi.mocked(invoke).mockResolvedValue({ mnemonic: exportedMnemonicFromBackend, passphrase_protected: false, warning: null }); // Backend returns mnemonic
       vi.mocked(mockAvailableFileSystem.pickSaveFile).mockResolvedValue(mockSavePath);
       vi.mocked(mockAvailableFileSystem.writeFile).mockResolvedValue(undefined); // Write succeeds
 
@@ -275,7 +275,7 @@ describe('WalletBackupDemoCard Component', () => {
       const exportedMnemonicFromBackend = 'export different winner thank wave sausage worth useful legal winner thank yellow';
       const writeError = new Error('File write failed'); // This error object will be caught
       vi.mocked(ask).mockResolvedValue(true); 
-      vi.mocked(invoke).mockResolvedValue(exportedMnemonicFromBackend); 
+      vi.mocked(invoke).mockResolvedValue({ mnemonic: exportedMnemonicFromBackend, passphrase_protected: false, warning: null }); 
       vi.mocked(mockAvailableFileSystem.pickSaveFile).mockResolvedValue(mockSavePath);
       // Ensure this mock is correctly awaited in the component if it's truly async
       vi.mocked(mockAvailableFileSystem.writeFile).mockRejectedValueOnce(writeError);
@@ -0,0 +1,249 @@
+// --- Content Key Epochs ---
+//
+// Lets a content key be rotated after it leaks. Each content id has a sequence of key epochs;
+// epoch N's content master key comes from derive_content_master_key_for_epoch and its slice key
+// from derive_symmetric_content_key, exactly as for the unversioned hierarchy (epoch 0 *is* the
+// unversioned hierarchy). Ciphertexts record the epoch they were written under:
+//   magic "PNCE" (4) || version (1) || epoch (4, big-endian) || envelope
+// The epoch is not separately authenticated: it selects the key, and the envelope's key id and
+// AEAD tag make any other key fail to decrypt.
+
+use crate::{
+    derive_content_master_key_for_epoch, derive_symmetric_content_key, open_envelope, seal_envelope,
+    CryptoError, Envelope, RootIdentitySecret, SymKey, SymmetricAlgorithm,
+};
+use zeroize::Zeroizing;
+
+pub const CONTENT_CIPHERTEXT_MAGIC: [u8; 4] = *b"PNCE";
+pub const CONTENT_CIPHERTEXT_VERSION: u8 = 1;
+const CONTENT_CIPHERTEXT_HEADER_BYTES: usize = CONTENT_CIPHERTEXT_MAGIC.len() + 1 + 4;
+
+// The epochs a reader accepts: the current one and up to `accepted_older` epochs before it.
+// Epochs newer than `current` are always rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EpochWindow {
+    current: u32,
+    accepted_older: u32,
+}
+
+impl EpochWindow {
+    pub fn new(current: u32, accepted_older: u32) -> Self {
+        Self { current, accepted_older }
+    }
+
+    // Accepts only the current epoch
+    pub fn current_only(current: u32) -> Self {
+        Self::new(current, 0)
+    }
+
+    pub fn current(&self) -> u32 {
+        self.current
+    }
+
+    pub fn oldest(&self) -> u32 {
+        self.current.saturating_sub(self.accepted_older)
+    }
+
+    pub fn accepts(&self, epoch: u32) -> bool {
+        (self.oldest()..=self.current).contains(&epoch)
+    }
+
+    fn check(&self, epoch: u32) -> Result<(), CryptoError> {
+        if self.accepts(epoch) {
+            Ok(())
+        } else {
+            Err(CryptoError::ContentEpochRejected { epoch, oldest: self.oldest(), current: self.current })
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentCiphertext {
+    epoch: u32,
+    envelope: Envelope,
+}
+
+impl ContentCiphertext {
+    pub fn epoch(&self) -> u32 {
+        self.epoch
+    }
+
+    pub fn envelope(&self) -> &Envelope {
+        &self.envelope
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let envelope = self.envelope.to_bytes();
+        let mut bytes = Vec::with_capacity(CONTENT_CIPHERTEXT_HEADER_BYTES + envelope.len());
+        bytes.extend_from_slice(&CONTENT_CIPHERTEXT_MAGIC);
+        bytes.push(CONTENT_CIPHERTEXT_VERSION);
+        bytes.extend_from_slice(&self.epoch.to_be_bytes());
+        bytes.extend_from_slice(&envelope);
+        bytes
+    }
+
+    /// Parses a serialized content ciphertext, including the envelope it carries.
+    ///
+    /// # Returns
+    /// * `Ok(ContentCiphertext)` if the header and envelope are well-formed.
+    /// * `Err(CryptoError::MalformedContentCiphertext)` if the header is wrong or truncated.
+    /// * Any `Envelope::parse` error for the embedded envelope.
+    pub fn parse(bytes: &[u8]) -> Result<Self, CryptoError> {
+        if bytes.len() < CONTENT_CIPHERTEXT_HEADER_BYTES {
+            return Err(CryptoError::MalformedContentCiphertext("truncated header".to_string()));
+        }
+        let (header, envelope) = bytes.split_at(CONTENT_CIPHERTEXT_HEADER_BYTES);
+        if header[..4] != CONTENT_CIPHERTEXT_MAGIC {
+            return Err(CryptoError::MalformedContentCiphertext("invalid magic bytes".to_string()));
+        }
+        if header[4] != CONTENT_CIPHERTEXT_VERSION {
+            return Err(CryptoError::MalformedContentCiphertext(format!("unsupported version {}", header[4])));
+        }
+        let epoch = u32::from_be_bytes(header[5..].try_into().expect("header has a 4-byte epoch"));
+        Ok(Self { epoch, envelope: Envelope::parse(envelope)? })
+    }
+}
+
+fn content_key_for_epoch(rik: &RootIdentitySecret, content_id: &[u8], epoch: u32) -> Result<SymKey, CryptoError> {
+    derive_symmetric_content_key(&derive_content_master_key_for_epoch(rik, content_id, epoch)?)
+}
+
+/// Encrypts content under the slice key of the given epoch.
+///
+/// # Returns
+/// * `Ok(ContentCiphertext)` recording `epoch`.
+/// * `Err(CryptoError)` if key derivation or encryption fails.
+pub fn encrypt_content(
+    rik: &RootIdentitySecret,
+    content_id: &[u8],
+    epoch: u32,
+    plaintext: &[u8],
+) -> Result<ContentCiphertext, CryptoError> {
+    let key = content_key_for_epoch(rik, content_id, epoch)?;
+    let envelope = seal_envelope(&key, SymmetricAlgorithm::XChaCha20Poly1305, plaintext, None)?;
+    Ok(ContentCiphertext { epoch, envelope })
+}
+
+/// Decrypts content written under any epoch `window` accepts.
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` with the plaintext.
+/// * `Err(CryptoError::ContentEpochRejected)` if the ciphertext's epoch is outside `window`.
+/// * `Err(CryptoError::EnvelopeKeyMismatch)` / `Err(CryptoError::DecryptionError)` if it was not
+///   encrypted for this content id and epoch, or was tampered with.
+pub fn decrypt_content(
+    rik: &RootIdentitySecret,
+    content_id: &[u8],
+    ciphertext: &ContentCiphertext,
+    window: &EpochWindow,
+) -> Result<Vec<u8>, CryptoError> {
+    window.check(ciphertext.epoch)?;
+    let key = content_key_for_epoch(rik, content_id, ciphertext.epoch)?;
+    open_envelope(&key, &ciphertext.envelope)
+}
+
+/// Re-encrypts content from its epoch N to epoch N + 1.
+/// Bump `window.current()` first: the new epoch must not be newer than the current one, or
+/// readers would reject the result.
+///
+/// # Returns
+/// * `Ok(ContentCiphertext)` under epoch N + 1.
+/// * `Err(CryptoError::ContentEpochRejected)` if epoch N is outside `window` or N + 1 is newer
+///   than `window.current()`.
+/// * Any `decrypt_content` error for the existing ciphertext.
+pub fn rotate_content(
+    rik: &RootIdentitySecret,
+    content_id: &[u8],
+    ciphertext: &ContentCiphertext,
+    window: &EpochWindow,
+) -> Result<ContentCiphertext, CryptoError> {
+    let next_epoch = ciphertext
+        .epoch
+        .checked_add(1)
+        .filter(|next| *next <= window.current)
+        .ok_or(CryptoError::ContentEpochRejected {
+            epoch: ciphertext.epoch,
+            oldest: window.oldest(),
+            current: window.current,
+        })?;
+    let plaintext = Zeroizing::new(decrypt_content(rik, content_id, ciphertext, window)?);
+    encrypt_content(rik, content_id, next_epoch, &plaintext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{derive_content_master_key, symmetric_key_id};
+
+    fn test_rik() -> RootIdentitySecret {
+        RootIdentitySecret::from_bytes([7u8; 32])
+    }
+
+    #[test]
+    fn test_epoch_zero_is_the_original_hierarchy() {
+        let rik = test_rik();
+        let original = derive_symmetric_content_key(&derive_content_master_key(&rik, b"doc").unwrap()).unwrap();
+        assert_eq!(content_key_for_epoch(&rik, b"doc", 0).unwrap(), original);
+        assert_ne!(content_key_for_epoch(&rik, b"doc", 1).unwrap(), original);
+        assert_ne!(content_key_for_epoch(&rik, b"doc", 1).unwrap(), content_key_for_epoch(&rik, b"doc", 2).unwrap());
+
+        // Content encrypted directly under the epoch 0 slice key stays readable
+        let envelope = seal_envelope(&original, SymmetricAlgorithm::XChaCha20Poly1305, b"legacy", None).unwrap();
+        let ciphertext = ContentCiphertext { epoch: 0, envelope };
+        assert_eq!(decrypt_content(&rik, b"doc", &ciphertext, &EpochWindow::new(0, 0)).unwrap(), b"legacy");
+    }
+
+    #[test]
+    fn test_rotation_and_window() {
+        let rik = test_rik();
+        let epoch_0 = encrypt_content(&rik, b"doc", 0, b"slice data").unwrap();
+
+        // Rotation needs the window to have moved on first
+        assert!(matches!(
+            rotate_content(&rik, b"doc", &epoch_0, &EpochWindow::current_only(0)),
+            Err(CryptoError::ContentEpochRejected { epoch: 0, .. })
+        ));
+
+        let window = EpochWindow::new(1, 1);
+        let epoch_1 = rotate_content(&rik, b"doc", &epoch_0, &window).unwrap();
+        assert_eq!(epoch_1.epoch(), 1);
+        assert_ne!(epoch_1.envelope().key_id(), epoch_0.envelope().key_id());
+        assert_eq!(decrypt_content(&rik, b"doc", &epoch_1, &window).unwrap(), b"slice data");
+        assert_eq!(decrypt_content(&rik, b"doc", &epoch_0, &window).unwrap(), b"slice data");
+
+        // Once the window no longer covers epoch 0, its ciphertexts are refused
+        let window = EpochWindow::current_only(1);
+        assert_eq!(
+            decrypt_content(&rik, b"doc", &epoch_0, &window).unwrap_err(),
+            CryptoError::ContentEpochRejected { epoch: 0, oldest: 1, current: 1 }
+        );
+        // Epochs ahead of the reader are refused too
+        let epoch_2 = encrypt_content(&rik, b"doc", 2, b"slice data").unwrap();
+        assert!(!window.accepts(2));
+        assert!(decrypt_content(&rik, b"doc", &epoch_2, &window).is_err());
+    }
+
+    #[test]
+    fn test_tampered_epoch_fails() {
+        let rik = test_rik();
+        let ciphertext = encrypt_content(&rik, b"doc", 1, b"slice data").unwrap();
+        let mut bytes = ciphertext.to_bytes();
+        assert_eq!(ContentCiphertext::parse(&bytes).unwrap(), ciphertext);
+
+        bytes[8] = 2; // epoch 1 -> 2
+        let relabeled = ContentCiphertext::parse(&bytes).unwrap();
+        assert_eq!(
+            decrypt_content(&rik, b"doc", &relabeled, &EpochWindow::new(2, 2)).unwrap_err(),
+            CryptoError::EnvelopeKeyMismatch
+        );
+        // A different content id derives a different key for the same epoch
+        assert_ne!(
+            symmetric_key_id(&content_key_for_epoch(&rik, b"other", 1).unwrap()),
+            *ciphertext.envelope().key_id()
+        );
+        assert!(matches!(
+            ContentCiphertext::parse(&bytes[..6]),
+            Err(CryptoError::MalformedContentCiphertext(_))
+        ));
+    }
+}
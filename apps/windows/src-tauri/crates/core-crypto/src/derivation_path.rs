@@ -0,0 +1,467 @@
+// --- Derivation Paths ---
+//
+// Names every key in the HKDF hierarchy with a path from the master seed, so callers can ask for
+// "m/identity/content/<id>/symmetric" instead of chaining the derive_* functions by hand:
+//   m                                   master seed
+//   m/identity                          root identity secret
+//   m/identity/signing/<purpose>        identity signing keypair
+//   m/identity/key-exchange/<purpose>   key exchange (X25519) keypair
+//   m/identity/content/<id>             content master key
+//   m/identity/content/<id>/epoch/<n>   content master key for key epoch n
+//   m/identity/content/<id>/symmetric   symmetric content key
+//   m/identity/content/<id>/token       token signing keypair
+// Symmetric and token keys can also hang off an epoch ("m/identity/content/<id>/epoch/2/token").
+// Epoch 0 derives the same key as the content master key without an epoch segment.
+// Each step calls the matching derive_* function, so a path yields exactly the key the direct
+// call does. Purposes and content ids are written as-is when they only use path-safe characters
+// (ASCII letters, digits, '-', '_', '.'); any other content id is written as "hex:<bytes>".
+
+use crate::{
+    derive_content_master_key, derive_content_master_key_for_epoch, derive_identity_signing_keypair,
+    derive_key_exchange_keypair, derive_root_identity_secret, derive_symmetric_content_key,
+    derive_token_signing_keypair, ContentMasterKey, CryptoError, KeyExchangePublicKey,
+    KeyExchangeSecretKey, MasterSeed, RootIdentitySecret, SigningPublicKey, SigningSecretKey, SymKey,
+};
+use std::fmt;
+use std::str::FromStr;
+
+const PATH_ROOT: &str = "m";
+const HEX_SEGMENT_PREFIX: &str = "hex:";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    Identity,
+    Signing(String),
+    KeyExchange(String),
+    Content(Vec<u8>),
+    // Only valid directly after a content segment
+    Epoch(u32),
+    Symmetric,
+    Token,
+}
+
+// The kind of key a path ends at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DerivedKeyKind {
+    RootIdentity,
+    IdentitySigning,
+    KeyExchange,
+    ContentMaster,
+    SymmetricContent,
+    TokenSigning,
+}
+
+#[derive(Debug)]
+pub enum DerivedKey {
+    RootIdentity(RootIdentitySecret),
+    IdentitySigning(SigningSecretKey, SigningPublicKey),
+    KeyExchange(KeyExchangeSecretKey, KeyExchangePublicKey),
+    ContentMaster(ContentMasterKey),
+    SymmetricContent(SymKey),
+    TokenSigning(SigningSecretKey, SigningPublicKey),
+}
+
+impl DerivedKey {
+    pub fn kind(&self) -> DerivedKeyKind {
+        match self {
+            DerivedKey::RootIdentity(_) => DerivedKeyKind::RootIdentity,
+            DerivedKey::IdentitySigning(..) => DerivedKeyKind::IdentitySigning,
+            DerivedKey::KeyExchange(..) => DerivedKeyKind::KeyExchange,
+            DerivedKey::ContentMaster(_) => DerivedKeyKind::ContentMaster,
+            DerivedKey::SymmetricContent(_) => DerivedKeyKind::SymmetricContent,
+            DerivedKey::TokenSigning(..) => DerivedKeyKind::TokenSigning,
+        }
+    }
+}
+
+// A path that is known to follow the hierarchy above; every constructor validates it
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DerivationPath {
+    segments: Vec<PathSegment>,
+}
+
+impl DerivationPath {
+    pub fn root_identity() -> Self {
+        Self { segments: vec![PathSegment::Identity] }
+    }
+
+    pub fn identity_signing(purpose: &str) -> Result<Self, CryptoError> {
+        Self::from_segments(vec![PathSegment::Identity, PathSegment::Signing(purpose.to_string())])
+    }
+
+    pub fn key_exchange(purpose: &str) -> Result<Self, CryptoError> {
+        Self::from_segments(vec![PathSegment::Identity, PathSegment::KeyExchange(purpose.to_string())])
+    }
+
+    pub fn content_master(content_id: &[u8]) -> Self {
+        Self { segments: vec![PathSegment::Identity, PathSegment::Content(content_id.to_vec())] }
+    }
+
+    pub fn content_master_for_epoch(content_id: &[u8], epoch: u32) -> Self {
+        Self::content_master(content_id).child(PathSegment::Epoch(epoch))
+    }
+
+    pub fn symmetric_content(content_id: &[u8]) -> Self {
+        Self::content_master(content_id).child(PathSegment::Symmetric)
+    }
+
+    pub fn token_signing(content_id: &[u8]) -> Self {
+        Self::content_master(content_id).child(PathSegment::Token)
+    }
+
+    pub fn from_segments(segments: Vec<PathSegment>) -> Result<Self, CryptoError> {
+        let path = Self { segments };
+        path.validate()?;
+        Ok(path)
+    }
+
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    pub fn kind(&self) -> DerivedKeyKind {
+        match self.segments.last() {
+            Some(PathSegment::Identity) => DerivedKeyKind::RootIdentity,
+            Some(PathSegment::Signing(_)) => DerivedKeyKind::IdentitySigning,
+            Some(PathSegment::KeyExchange(_)) => DerivedKeyKind::KeyExchange,
+            Some(PathSegment::Content(_) | PathSegment::Epoch(_)) => DerivedKeyKind::ContentMaster,
+            Some(PathSegment::Symmetric) => DerivedKeyKind::SymmetricContent,
+            Some(PathSegment::Token) => DerivedKeyKind::TokenSigning,
+            None => unreachable!("validated paths are never empty"),
+        }
+    }
+
+    // Only used where the parent kind is known to accept the segment
+    fn child(mut self, segment: PathSegment) -> Self {
+        self.segments.push(segment);
+        self
+    }
+
+    fn validate(&self) -> Result<(), CryptoError> {
+        let invalid = |reason: String| Err(CryptoError::InvalidDerivationPath(reason));
+        let mut parent: Option<DerivedKeyKind> = None;
+        let mut previous: Option<&PathSegment> = None;
+        for segment in &self.segments {
+            let kind = match (parent, segment) {
+                (None, PathSegment::Identity) => DerivedKeyKind::RootIdentity,
+                (Some(DerivedKeyKind::RootIdentity), PathSegment::Signing(purpose) | PathSegment::KeyExchange(purpose))
+                    if !is_path_safe(purpose.as_bytes()) =>
+                {
+                    return invalid(format!("purpose {:?} has characters a path can't hold", purpose));
+                }
+                (Some(DerivedKeyKind::RootIdentity), PathSegment::Signing(_)) => DerivedKeyKind::IdentitySigning,
+                (Some(DerivedKeyKind::RootIdentity), PathSegment::KeyExchange(_)) => DerivedKeyKind::KeyExchange,
+                (Some(DerivedKeyKind::RootIdentity), PathSegment::Content(_)) => DerivedKeyKind::ContentMaster,
+                (Some(DerivedKeyKind::ContentMaster), PathSegment::Epoch(_))
+                    if matches!(previous, Some(PathSegment::Content(_))) =>
+                {
+                    DerivedKeyKind::ContentMaster
+                }
+                (Some(DerivedKeyKind::ContentMaster), PathSegment::Symmetric) => DerivedKeyKind::SymmetricContent,
+                (Some(DerivedKeyKind::ContentMaster), PathSegment::Token) => DerivedKeyKind::TokenSigning,
+                (parent, segment) => {
+                    return invalid(format!("{:?} can't be derived from {:?}", segment, parent));
+                }
+            };
+            parent = Some(kind);
+            previous = Some(segment);
+        }
+        if parent.is_none() {
+            return invalid("path has no segments below the master seed".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(PATH_ROOT)?;
+        for segment in &self.segments {
+            match segment {
+                PathSegment::Identity => f.write_str("/identity")?,
+                PathSegment::Signing(purpose) => write!(f, "/signing/{}", purpose)?,
+                PathSegment::KeyExchange(purpose) => write!(f, "/key-exchange/{}", purpose)?,
+                PathSegment::Content(id) if is_path_safe(id) => {
+                    // is_path_safe only admits ASCII
+                    write!(f, "/content/{}", std::str::from_utf8(id).expect("path-safe ids are ASCII"))?
+                }
+                PathSegment::Content(id) => write!(f, "/content/{}{}", HEX_SEGMENT_PREFIX, hex::encode(id))?,
+                PathSegment::Epoch(epoch) => write!(f, "/epoch/{}", epoch)?,
+                PathSegment::Symmetric => f.write_str("/symmetric")?,
+                PathSegment::Token => f.write_str("/token")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = CryptoError;
+
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| CryptoError::InvalidDerivationPath(format!("{}: {}", reason, path));
+        let mut parts = path.split('/');
+        if parts.next() != Some(PATH_ROOT) {
+            return Err(invalid("path must start at \"m\""));
+        }
+
+        let mut segments = Vec::new();
+        while let Some(part) = parts.next() {
+            let segment = match part {
+                "identity" => PathSegment::Identity,
+                "symmetric" => PathSegment::Symmetric,
+                "token" => PathSegment::Token,
+                "signing" => {
+                    let purpose = parts.next().ok_or_else(|| invalid("signing needs a purpose"))?;
+                    PathSegment::Signing(purpose.to_string())
+                }
+                "key-exchange" => {
+                    let purpose = parts.next().ok_or_else(|| invalid("key-exchange needs a purpose"))?;
+                    PathSegment::KeyExchange(purpose.to_string())
+                }
+                "content" => {
+                    let id = parts.next().ok_or_else(|| invalid("content needs an id"))?;
+                    PathSegment::Content(parse_content_id(id).ok_or_else(|| invalid("invalid content id"))?)
+                }
+                "epoch" => {
+                    let epoch = parts.next().ok_or_else(|| invalid("epoch needs a number"))?;
+                    // Only the canonical decimal form, so every path has one spelling
+                    let number = epoch.parse::<u32>().ok().filter(|n| n.to_string() == epoch);
+                    PathSegment::Epoch(number.ok_or_else(|| invalid("invalid epoch"))?)
+                }
+                _ => return Err(invalid("unknown path segment")),
+            };
+            segments.push(segment);
+        }
+        Self::from_segments(segments)
+    }
+}
+
+fn is_path_safe(bytes: &[u8]) -> bool {
+    !bytes.is_empty() && bytes.iter().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'))
+}
+
+fn parse_content_id(text: &str) -> Option<Vec<u8>> {
+    match text.strip_prefix(HEX_SEGMENT_PREFIX) {
+        Some(encoded) => hex::decode(encoded).ok(),
+        None if is_path_safe(text.as_bytes()) => Some(text.as_bytes().to_vec()),
+        None => None,
+    }
+}
+
+/// Derives the key at `path` from the master seed by walking the HKDF hierarchy.
+///
+/// # Returns
+/// * `Ok(DerivedKey)` whose variant matches `path.kind()`.
+/// * `Err(CryptoError::KeyDerivationError)` if an HKDF step fails.
+pub fn derive_path(master_seed: &MasterSeed, path: &DerivationPath) -> Result<DerivedKey, CryptoError> {
+    let mut current: Option<DerivedKey> = None;
+    let mut segments = path.segments.iter().peekable();
+    while let Some(segment) = segments.next() {
+        current = Some(match (current, segment) {
+            (None, PathSegment::Identity) => DerivedKey::RootIdentity(derive_root_identity_secret(master_seed)?),
+            (Some(DerivedKey::RootIdentity(rik)), PathSegment::Signing(purpose)) => {
+                let (secret, public) = derive_identity_signing_keypair(&rik, purpose)?;
+                DerivedKey::IdentitySigning(secret, public)
+            }
+            (Some(DerivedKey::RootIdentity(rik)), PathSegment::KeyExchange(purpose)) => {
+                let (secret, public) = derive_key_exchange_keypair(&rik, purpose)?;
+                DerivedKey::KeyExchange(secret, public)
+            }
+            (Some(DerivedKey::RootIdentity(rik)), PathSegment::Content(id)) => {
+                // The epoch picks which content master key to derive from the RIK
+                match segments.next_if(|next| matches!(next, PathSegment::Epoch(_))) {
+                    Some(PathSegment::Epoch(epoch)) => {
+                        DerivedKey::ContentMaster(derive_content_master_key_for_epoch(&rik, id, *epoch)?)
+                    }
+                    _ => DerivedKey::ContentMaster(derive_content_master_key(&rik, id)?),
+                }
+            }
+            (Some(DerivedKey::ContentMaster(cmk)), PathSegment::Symmetric) => {
+                DerivedKey::SymmetricContent(derive_symmetric_content_key(&cmk)?)
+            }
+            (Some(DerivedKey::ContentMaster(cmk)), PathSegment::Token) => {
+                let (secret, public) = derive_token_signing_keypair(&cmk)?;
+                DerivedKey::TokenSigning(secret, public)
+            }
+            _ => unreachable!("validated paths only contain derivable steps"),
+        });
+    }
+    Ok(current.expect("validated paths are never empty"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Outputs of the derive_* chain for test_seed() and content id "content-1"
+    const ROOT_IDENTITY_HEX: &str = "6ef80f0c96e49f1547082c8df49b4db8e49b312f77e479ec9f3634c081274078";
+    const IDENTITY_SIGNING_PUBLIC_HEX: &str = "070d2445422e2d822d5ed3d5a7d30c5de9ea7d9f5000ad404703288b1e094dd6";
+    const CONTENT_MASTER_HEX: &str = "5b926aea630a592f4fedfbfc246f28ad6e68b2ac028c2ae2ef7a747f1681023d";
+    const SYMMETRIC_CONTENT_HEX: &str = "40ffe66c1db2b243bf0eb521595639a646f4f100a17d5df23144bd5f689fc144";
+    const TOKEN_SIGNING_PUBLIC_HEX: &str = "e026d5129d191b8295f19ff53393283f3f16c0ac80b945c7121798e30cec18a8";
+
+    fn test_seed() -> MasterSeed {
+        MasterSeed::from_bytes((0u8..64).collect())
+    }
+
+    #[test]
+    fn test_paths_match_direct_derivations() {
+        // Pins every derive_* step to its path so the two APIs can never drift apart, and pins
+        // the outputs themselves so existing keys stay derivable
+        let seed = test_seed();
+        let rik = derive_root_identity_secret(&seed).unwrap();
+        let cmk = derive_content_master_key(&rik, b"content-1").unwrap();
+
+        let DerivedKey::RootIdentity(path_rik) = derive_path(&seed, &"m/identity".parse().unwrap()).unwrap() else {
+            panic!("expected a root identity secret");
+        };
+        assert_eq!(path_rik, rik);
+        assert_eq!(hex::encode(rik.expose_secret()), ROOT_IDENTITY_HEX);
+
+        let path = "m/identity/signing/primary-chain-signing".parse().unwrap();
+        let DerivedKey::IdentitySigning(_, public) = derive_path(&seed, &path).unwrap() else {
+            panic!("expected an identity signing keypair");
+        };
+        assert_eq!(public, derive_identity_signing_keypair(&rik, "primary-chain-signing").unwrap().1);
+        assert_eq!(hex::encode(public.as_bytes()), IDENTITY_SIGNING_PUBLIC_HEX);
+
+        let DerivedKey::ContentMaster(path_cmk) =
+            derive_path(&seed, &"m/identity/content/content-1".parse().unwrap()).unwrap()
+        else {
+            panic!("expected a content master key");
+        };
+        assert_eq!(path_cmk, cmk);
+        assert_eq!(hex::encode(cmk.expose_secret()), CONTENT_MASTER_HEX);
+
+        let DerivedKey::SymmetricContent(key) =
+            derive_path(&seed, &"m/identity/content/content-1/symmetric".parse().unwrap()).unwrap()
+        else {
+            panic!("expected a symmetric content key");
+        };
+        assert_eq!(key, derive_symmetric_content_key(&cmk).unwrap());
+        assert_eq!(hex::encode(key.expose_secret()), SYMMETRIC_CONTENT_HEX);
+
+        let DerivedKey::TokenSigning(_, public) =
+            derive_path(&seed, &"m/identity/content/content-1/token".parse().unwrap()).unwrap()
+        else {
+            panic!("expected a token signing keypair");
+        };
+        assert_eq!(public, derive_token_signing_keypair(&cmk).unwrap().1);
+        assert_eq!(hex::encode(public.as_bytes()), TOKEN_SIGNING_PUBLIC_HEX);
+    }
+
+
+    #[test]
+    fn test_key_exchange_path() {
+        let seed = test_seed();
+        let rik = derive_root_identity_secret(&seed).unwrap();
+        let path = "m/identity/key-exchange/primary-key-exchange".parse().unwrap();
+        let DerivedKey::KeyExchange(secret, public) = derive_path(&seed, &path).unwrap() else {
+            panic!("expected a key exchange keypair");
+        };
+        let (expected_secret, expected_public) = derive_key_exchange_keypair(&rik, "primary-key-exchange").unwrap();
+        assert_eq!(secret, expected_secret);
+        assert_eq!(public, expected_public);
+    }
+
+    #[test]
+    fn test_epoch_paths() {
+        let seed = test_seed();
+        let rik = derive_root_identity_secret(&seed).unwrap();
+        let content_master = |text: &str| match derive_path(&seed, &text.parse().unwrap()).unwrap() {
+            DerivedKey::ContentMaster(cmk) => cmk,
+            other => panic!("expected a content master key, got {:?}", other.kind()),
+        };
+
+        assert_eq!(content_master("m/identity/content/content-1/epoch/0"), content_master("m/identity/content/content-1"));
+        assert_eq!(
+            content_master("m/identity/content/content-1/epoch/5"),
+            derive_content_master_key_for_epoch(&rik, b"content-1", 5).unwrap()
+        );
+
+        let path = "m/identity/content/content-1/epoch/5/symmetric".parse().unwrap();
+        let DerivedKey::SymmetricContent(key) = derive_path(&seed, &path).unwrap() else {
+            panic!("expected a symmetric content key");
+        };
+        let cmk = derive_content_master_key_for_epoch(&rik, b"content-1", 5).unwrap();
+        assert_eq!(key, derive_symmetric_content_key(&cmk).unwrap());
+    }
+
+    #[test]
+    fn test_typed_constructors_round_trip() {
+        let paths = [
+            (DerivationPath::root_identity(), "m/identity", DerivedKeyKind::RootIdentity),
+            (
+                DerivationPath::identity_signing("login").unwrap(),
+                "m/identity/signing/login",
+                DerivedKeyKind::IdentitySigning,
+            ),
+            (
+                DerivationPath::key_exchange("account-2").unwrap(),
+                "m/identity/key-exchange/account-2",
+                DerivedKeyKind::KeyExchange,
+            ),
+            (DerivationPath::content_master(b"doc_1.pdf"), "m/identity/content/doc_1.pdf", DerivedKeyKind::ContentMaster),
+            (
+                DerivationPath::symmetric_content(&[0x00, 0xff]),
+                "m/identity/content/hex:00ff/symmetric",
+                DerivedKeyKind::SymmetricContent,
+            ),
+            (
+                DerivationPath::content_master_for_epoch(b"doc", 3),
+                "m/identity/content/doc/epoch/3",
+                DerivedKeyKind::ContentMaster,
+            ),
+            (DerivationPath::token_signing(b"a/b"), "m/identity/content/hex:612f62/token", DerivedKeyKind::TokenSigning),
+        ];
+        for (path, text, kind) in paths {
+            assert_eq!(path.to_string(), text);
+            assert_eq!(text.parse::<DerivationPath>().unwrap(), path);
+            assert_eq!(path.kind(), kind);
+            assert_eq!(derive_path(&test_seed(), &path).unwrap().kind(), kind);
+        }
+    }
+
+    #[test]
+    fn test_invalid_paths_rejected() {
+        for text in [
+            "",
+            "m",
+            "n/identity",
+            "m/content/abc",
+            "m/identity/symmetric",
+            "m/identity/signing",
+            "m/identity/signing/",
+            "m/identity/key-exchange",
+            "m/identity/key-exchange/account/symmetric",
+            "m/identity/content/abc/token/symmetric",
+            "m/identity/content/hex:zz",
+            "m/identity/content/a b",
+            "m/identity/unknown",
+            "m/identity/content/abc/epoch",
+            "m/identity/content/abc/epoch/01",
+            "m/identity/content/abc/epoch/-1",
+            "m/identity/content/abc/epoch/1/epoch/2",
+            "m/identity/content/abc/symmetric/epoch/1",
+        ] {
+            assert!(
+                matches!(text.parse::<DerivationPath>(), Err(CryptoError::InvalidDerivationPath(_))),
+                "{:?} should be rejected",
+                text
+            );
+        }
+        assert!(matches!(
+            DerivationPath::identity_signing("has space"),
+            Err(CryptoError::InvalidDerivationPath(_))
+        ));
+        assert!(matches!(
+            DerivationPath::key_exchange("a/b"),
+            Err(CryptoError::InvalidDerivationPath(_))
+        ));
+        assert!(matches!(
+            DerivationPath::from_segments(vec![PathSegment::Token]),
+            Err(CryptoError::InvalidDerivationPath(_))
+        ));
+    }
+}
@@ -0,0 +1,401 @@
+// --- Versioned Ciphertext Envelope ---
+//
+// A self-describing container for symmetric ciphertexts, so a stored blob carries everything
+// except the key that is needed to decrypt it: which algorithm was used, which key it was
+// encrypted under, the nonce and any associated data.
+//
+// Binary layout (all integers big-endian):
+//   magic "PNLE" (4) || version (1) || algorithm id (1) || key id (16) || nonce (12 or 24)
+//   || associated data length (4) || associated data || ciphertext (including 16-byte tag)
+//
+// Everything before the ciphertext (the header) is passed to the AEAD as associated data, so
+// changing the version, algorithm, key id, nonce or associated data makes decryption fail.
+
+use crate::{
+    decrypt_symmetric, decrypt_symmetric_xchacha, encrypt_symmetric, encrypt_symmetric_xchacha,
+    generate_nonce, generate_xnonce, CryptoError, Nonce, SymKey, XNonce, NONCE_BYTES, XNONCE_BYTES,
+};
+use sha3::{Digest, Sha3_256};
+
+pub const ENVELOPE_MAGIC: [u8; 4] = *b"PNLE";
+pub const ENVELOPE_VERSION: u8 = 1;
+pub const KEY_ID_BYTES: usize = 16;
+const AEAD_TAG_BYTES: usize = 16;
+
+// Short, non-secret identifier for a symmetric key
+pub type KeyId = [u8; KEY_ID_BYTES];
+
+// Algorithm identifiers. The numeric values are part of the stored format and must never be reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymmetricAlgorithm {
+    ChaCha20Poly1305,
+    XChaCha20Poly1305,
+}
+
+impl SymmetricAlgorithm {
+    pub fn id(&self) -> u8 {
+        match self {
+            SymmetricAlgorithm::ChaCha20Poly1305 => 1,
+            SymmetricAlgorithm::XChaCha20Poly1305 => 2,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Result<Self, CryptoError> {
+        match id {
+            1 => Ok(SymmetricAlgorithm::ChaCha20Poly1305),
+            2 => Ok(SymmetricAlgorithm::XChaCha20Poly1305),
+            other => Err(CryptoError::UnsupportedAlgorithm(other)),
+        }
+    }
+
+    pub fn nonce_len(&self) -> usize {
+        match self {
+            SymmetricAlgorithm::ChaCha20Poly1305 => NONCE_BYTES,
+            SymmetricAlgorithm::XChaCha20Poly1305 => XNONCE_BYTES,
+        }
+    }
+}
+
+/// Derives the public key id for a symmetric key.
+/// This is a truncated, domain-separated SHA3-256 hash, so it identifies the key without revealing it.
+pub fn symmetric_key_id(key: &SymKey) -> KeyId {
+    let mut hasher = Sha3_256::new();
+    hasher.update(b"paynless-symmetric-key-id-v1");
+    hasher.update(key.expose_secret());
+    let digest = hasher.finalize();
+    let mut key_id = [0u8; KEY_ID_BYTES];
+    key_id.copy_from_slice(&digest[..KEY_ID_BYTES]);
+    key_id
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Envelope {
+    algorithm: SymmetricAlgorithm,
+    key_id: KeyId,
+    nonce: Vec<u8>,
+    associated_data: Vec<u8>,
+    ciphertext: Vec<u8>,
+}
+
+impl Envelope {
+    pub fn algorithm(&self) -> SymmetricAlgorithm {
+        self.algorithm
+    }
+
+    pub fn key_id(&self) -> &KeyId {
+        &self.key_id
+    }
+
+    pub fn nonce(&self) -> &[u8] {
+        &self.nonce
+    }
+
+    pub fn associated_data(&self) -> &[u8] {
+        &self.associated_data
+    }
+
+    pub fn ciphertext(&self) -> &[u8] {
+        &self.ciphertext
+    }
+
+    // Serialized header, which doubles as the AEAD associated data
+    fn header_bytes(&self) -> Vec<u8> {
+        let mut header = Vec::with_capacity(
+            ENVELOPE_MAGIC.len() + 2 + KEY_ID_BYTES + self.nonce.len() + 4 + self.associated_data.len(),
+        );
+        header.extend_from_slice(&ENVELOPE_MAGIC);
+        header.push(ENVELOPE_VERSION);
+        header.push(self.algorithm.id());
+        header.extend_from_slice(&self.key_id);
+        header.extend_from_slice(&self.nonce);
+        header.extend_from_slice(&(self.associated_data.len() as u32).to_be_bytes());
+        header.extend_from_slice(&self.associated_data);
+        header
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.header_bytes();
+        bytes.extend_from_slice(&self.ciphertext);
+        bytes
+    }
+
+    /// Parses and strictly validates a serialized envelope.
+    /// This only checks the structure; authenticity is checked by `open_envelope`.
+    ///
+    /// # Returns
+    /// * `Ok(Envelope)` if the layout is well-formed.
+    /// * `Err(CryptoError::InvalidEnvelopeMagic)` if the bytes are not an envelope at all.
+    /// * `Err(CryptoError::UnsupportedEnvelopeVersion)` / `Err(CryptoError::UnsupportedAlgorithm)`
+    ///   for envelopes written by a newer format.
+    /// * `Err(CryptoError::MalformedEnvelope)` if any field is truncated.
+    pub fn parse(bytes: &[u8]) -> Result<Self, CryptoError> {
+        let mut reader = EnvelopeReader { bytes, pos: 0 };
+
+        if reader.take(ENVELOPE_MAGIC.len(), "magic")? != ENVELOPE_MAGIC {
+            return Err(CryptoError::InvalidEnvelopeMagic);
+        }
+        let version = reader.take(1, "version")?[0];
+        if version != ENVELOPE_VERSION {
+            return Err(CryptoError::UnsupportedEnvelopeVersion(version));
+        }
+        let algorithm = SymmetricAlgorithm::from_id(reader.take(1, "algorithm id")?[0])?;
+        let key_id: KeyId = reader
+            .take(KEY_ID_BYTES, "key id")?
+            .try_into()
+            .expect("slice has KEY_ID_BYTES length");
+        let nonce = reader.take(algorithm.nonce_len(), "nonce")?.to_vec();
+        let aad_len = u32::from_be_bytes(
+            reader
+                .take(4, "associated data length")?
+                .try_into()
+                .expect("slice has 4 bytes"),
+        ) as usize;
+        let associated_data = reader.take(aad_len, "associated data")?.to_vec();
+        let ciphertext = reader.rest().to_vec();
+        if ciphertext.len() < AEAD_TAG_BYTES {
+            return Err(CryptoError::MalformedEnvelope(
+                "ciphertext is shorter than the authentication tag".to_string(),
+            ));
+        }
+
+        Ok(Envelope {
+            algorithm,
+            key_id,
+            nonce,
+            associated_data,
+            ciphertext,
+        })
+    }
+}
+
+// Minimal cursor that turns short reads into MalformedEnvelope errors
+struct EnvelopeReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> EnvelopeReader<'a> {
+    fn take(&mut self, len: usize, field: &str) -> Result<&'a [u8], CryptoError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| CryptoError::MalformedEnvelope(format!("truncated {}", field)))?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn rest(&mut self) -> &'a [u8] {
+        let slice = &self.bytes[self.pos..];
+        self.pos = self.bytes.len();
+        slice
+    }
+}
+
+/// Encrypts `plaintext` under `key` with a fresh random nonce and wraps it in an envelope.
+/// `associated_data` is stored in the clear inside the envelope and authenticated with it.
+pub fn seal_envelope(
+    key: &SymKey,
+    algorithm: SymmetricAlgorithm,
+    plaintext: &[u8],
+    associated_data: Option<&[u8]>,
+) -> Result<Envelope, CryptoError> {
+    let associated_data = associated_data.unwrap_or_default();
+    if associated_data.len() > u32::MAX as usize {
+        return Err(CryptoError::EncryptionError(
+            "associated data too large for envelope".to_string(),
+        ));
+    }
+
+    let nonce = match algorithm {
+        SymmetricAlgorithm::ChaCha20Poly1305 => generate_nonce().to_vec(),
+        SymmetricAlgorithm::XChaCha20Poly1305 => generate_xnonce().to_vec(),
+    };
+    let mut envelope = Envelope {
+        algorithm,
+        key_id: symmetric_key_id(key),
+        nonce,
+        associated_data: associated_data.to_vec(),
+        ciphertext: Vec::new(),
+    };
+
+    let header = envelope.header_bytes();
+    envelope.ciphertext = match algorithm {
+        SymmetricAlgorithm::ChaCha20Poly1305 => {
+            let nonce: Nonce = envelope.nonce.as_slice().try_into().expect("nonce length matches algorithm");
+            encrypt_symmetric(key, plaintext, &nonce, Some(&header))?
+        }
+        SymmetricAlgorithm::XChaCha20Poly1305 => {
+            let nonce: XNonce = envelope.nonce.as_slice().try_into().expect("nonce length matches algorithm");
+            encrypt_symmetric_xchacha(key, plaintext, &nonce, Some(&header))?
+        }
+    };
+    Ok(envelope)
+}
+
+/// Decrypts an envelope.
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` with the plaintext.
+/// * `Err(CryptoError::EnvelopeKeyMismatch)` if the envelope names a different key id than `key`.
+/// * `Err(CryptoError::DecryptionError)` if the ciphertext or any header field was tampered with.
+pub fn open_envelope(key: &SymKey, envelope: &Envelope) -> Result<Vec<u8>, CryptoError> {
+    if envelope.key_id != symmetric_key_id(key) {
+        return Err(CryptoError::EnvelopeKeyMismatch);
+    }
+
+    let header = envelope.header_bytes();
+    match envelope.algorithm {
+        SymmetricAlgorithm::ChaCha20Poly1305 => {
+            let nonce: Nonce = envelope
+                .nonce
+                .as_slice()
+                .try_into()
+                .map_err(|_| CryptoError::InvalidNonceLength)?;
+            decrypt_symmetric(key, &envelope.ciphertext, &nonce, Some(&header))
+        }
+        SymmetricAlgorithm::XChaCha20Poly1305 => {
+            let nonce: XNonce = envelope
+                .nonce
+                .as_slice()
+                .try_into()
+                .map_err(|_| CryptoError::InvalidNonceLength)?;
+            decrypt_symmetric_xchacha(key, &envelope.ciphertext, &nonce, Some(&header))
+        }
+    }
+}
+
+/// Decrypts `envelope` with `old_key` and re-encrypts the plaintext under `new_key` and
+/// `new_algorithm`, keeping the original associated data. Used to migrate stored blobs
+/// between keys or algorithms.
+pub fn reseal_envelope(
+    old_key: &SymKey,
+    envelope: &Envelope,
+    new_key: &SymKey,
+    new_algorithm: SymmetricAlgorithm,
+) -> Result<Envelope, CryptoError> {
+    let plaintext = open_envelope(old_key, envelope)?;
+    seal_envelope(new_key, new_algorithm, &plaintext, Some(&envelope.associated_data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::generate_random_key;
+
+    #[test]
+    fn test_envelope_roundtrip_both_algorithms() {
+        let key = generate_random_key();
+        for algorithm in [SymmetricAlgorithm::ChaCha20Poly1305, SymmetricAlgorithm::XChaCha20Poly1305] {
+            let envelope = seal_envelope(&key, algorithm, b"stored for years", Some(b"content-42"))
+                .expect("Sealing failed");
+            assert_eq!(envelope.nonce().len(), algorithm.nonce_len());
+
+            let bytes = envelope.to_bytes();
+            let parsed = Envelope::parse(&bytes).expect("Parsing failed");
+            assert_eq!(parsed, envelope);
+            assert_eq!(parsed.algorithm(), algorithm);
+            assert_eq!(parsed.key_id(), &symmetric_key_id(&key));
+            assert_eq!(parsed.associated_data(), b"content-42");
+
+            let plaintext = open_envelope(&key, &parsed).expect("Opening failed");
+            assert_eq!(plaintext, b"stored for years");
+        }
+    }
+
+    #[test]
+    fn test_envelope_layout() {
+        let key = generate_random_key();
+        let envelope = seal_envelope(&key, SymmetricAlgorithm::XChaCha20Poly1305, b"abc", Some(b"aad"))
+            .expect("Sealing failed");
+        let bytes = envelope.to_bytes();
+
+        assert_eq!(&bytes[0..4], b"PNLE");
+        assert_eq!(bytes[4], ENVELOPE_VERSION);
+        assert_eq!(bytes[5], 2);
+        assert_eq!(&bytes[6..22], &symmetric_key_id(&key));
+        assert_eq!(&bytes[46..50], &3u32.to_be_bytes());
+        assert_eq!(&bytes[50..53], b"aad");
+        assert_eq!(bytes.len(), 53 + 3 + AEAD_TAG_BYTES);
+    }
+
+    #[test]
+    fn test_envelope_wrong_key() {
+        let key = generate_random_key();
+        let envelope = seal_envelope(&key, SymmetricAlgorithm::XChaCha20Poly1305, b"secret", None)
+            .expect("Sealing failed");
+
+        let result = open_envelope(&generate_random_key(), &envelope);
+        assert_eq!(result.unwrap_err(), CryptoError::EnvelopeKeyMismatch);
+    }
+
+    #[test]
+    fn test_envelope_header_is_authenticated() {
+        let key = generate_random_key();
+        let envelope = seal_envelope(&key, SymmetricAlgorithm::ChaCha20Poly1305, b"secret", Some(b"aad"))
+            .expect("Sealing failed");
+        let bytes = envelope.to_bytes();
+
+        // Flip a bit in the nonce (22..34) and in the associated data (38..41)
+        for index in [22, 38] {
+            let mut tampered = bytes.clone();
+            tampered[index] ^= 0x01;
+            let parsed = Envelope::parse(&tampered).expect("Structure is still valid");
+            assert_eq!(open_envelope(&key, &parsed).unwrap_err(), CryptoError::DecryptionError);
+        }
+    }
+
+    #[test]
+    fn test_envelope_parse_errors() {
+        let key = generate_random_key();
+        let bytes = seal_envelope(&key, SymmetricAlgorithm::XChaCha20Poly1305, b"secret", Some(b"aad"))
+            .expect("Sealing failed")
+            .to_bytes();
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert_eq!(Envelope::parse(&bad_magic).unwrap_err(), CryptoError::InvalidEnvelopeMagic);
+
+        let mut bad_version = bytes.clone();
+        bad_version[4] = 99;
+        assert_eq!(Envelope::parse(&bad_version).unwrap_err(), CryptoError::UnsupportedEnvelopeVersion(99));
+
+        let mut bad_algorithm = bytes.clone();
+        bad_algorithm[5] = 0;
+        assert_eq!(Envelope::parse(&bad_algorithm).unwrap_err(), CryptoError::UnsupportedAlgorithm(0));
+
+        // Every strict prefix must be rejected as malformed (or bad magic for the very short ones)
+        for len in 0..bytes.len() - AEAD_TAG_BYTES {
+            match Envelope::parse(&bytes[..len]) {
+                Err(CryptoError::MalformedEnvelope(_)) => {}
+                other => panic!("Expected MalformedEnvelope for length {}, got {:?}", len, other),
+            }
+        }
+
+        // An associated data length that runs past the end of the buffer
+        let mut bad_aad_len = bytes.clone();
+        bad_aad_len[46..50].copy_from_slice(&u32::MAX.to_be_bytes());
+        match Envelope::parse(&bad_aad_len) {
+            Err(CryptoError::MalformedEnvelope(msg)) => assert!(msg.contains("associated data")),
+            other => panic!("Expected MalformedEnvelope, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_reseal_envelope_migrates_key_and_algorithm() {
+        let old_key = generate_random_key();
+        let new_key = generate_random_key();
+        let envelope = seal_envelope(&old_key, SymmetricAlgorithm::ChaCha20Poly1305, b"legacy blob", Some(b"id"))
+            .expect("Sealing failed");
+
+        let migrated = reseal_envelope(&old_key, &envelope, &new_key, SymmetricAlgorithm::XChaCha20Poly1305)
+            .expect("Migration failed");
+
+        assert_eq!(migrated.algorithm(), SymmetricAlgorithm::XChaCha20Poly1305);
+        assert_eq!(migrated.key_id(), &symmetric_key_id(&new_key));
+        assert_eq!(migrated.associated_data(), b"id");
+        assert_eq!(open_envelope(&new_key, &migrated).unwrap(), b"legacy blob");
+        assert_eq!(open_envelope(&old_key, &migrated).unwrap_err(), CryptoError::EnvelopeKeyMismatch);
+    }
+}
@@ -0,0 +1,223 @@
+// --- Public Key Fingerprints ---
+//
+// A fingerprint is a SHA3-256 hash that identifies a public key, with renderings short enough
+// for people to compare out loud or side by side:
+//   fingerprint = SHA3-256("paynless-fingerprint" || version (1) || key type || public key)
+// The key type ("ed25519" or "x25519") keeps a signing key and a key exchange key with the same
+// bytes from sharing a fingerprint. Bumping FINGERPRINT_VERSION changes every fingerprint, so
+// renderings of different versions never match by accident.
+
+use crate::{KeyExchangePublicKey, SigningPublicKey};
+use bip39::Language;
+use sha3::{Digest, Sha3_256};
+
+pub const FINGERPRINT_VERSION: u8 = 1;
+pub const FINGERPRINT_BYTES: usize = 32;
+// Words in a safety phrase (12 * 11 = 132 bits of the fingerprint)
+pub const SAFETY_PHRASE_WORDS: usize = 12;
+
+const FINGERPRINT_DOMAIN: &[u8] = b"paynless-fingerprint";
+// Bytes of the fingerprint shown in the grouped base32 form (160 bits, 32 characters)
+const BASE32_FINGERPRINT_BYTES: usize = 20;
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+// Each party contributes 6 groups of 5 digits to a safety number
+const SAFETY_NUMBER_GROUPS_PER_KEY: usize = 6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Fingerprint {
+    version: u8,
+    bytes: [u8; FINGERPRINT_BYTES],
+}
+
+impl Fingerprint {
+    fn derive(key_type: &[u8], public_key: &[u8]) -> Self {
+        let mut hasher = Sha3_256::new();
+        hasher.update(FINGERPRINT_DOMAIN);
+        hasher.update([FINGERPRINT_VERSION]);
+        hasher.update(key_type);
+        hasher.update(public_key);
+        Self { version: FINGERPRINT_VERSION, bytes: hasher.finalize().into() }
+    }
+
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    pub fn as_bytes(&self) -> &[u8; FINGERPRINT_BYTES] {
+        &self.bytes
+    }
+
+    // The first 160 bits in base32, in groups of four: "ABCD EFGH ..." (8 groups)
+    pub fn to_base32_grouped(&self) -> String {
+        let encoded = base32_encode(&self.bytes[..BASE32_FINGERPRINT_BYTES]);
+        encoded
+            .as_bytes()
+            .chunks(4)
+            .map(|group| std::str::from_utf8(group).expect("base32 is ASCII"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    // The first 132 bits as BIP-39 English words, e.g. for reading out over a call
+    pub fn to_safety_phrase(&self) -> String {
+        let words = Language::English.word_list();
+        (0..SAFETY_PHRASE_WORDS)
+            .map(|i| words[read_bits(&self.bytes, i * 11, 11) as usize])
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+pub fn signing_key_fingerprint(public_key: &SigningPublicKey) -> Fingerprint {
+    Fingerprint::derive(b"ed25519", public_key.as_bytes())
+}
+
+pub fn key_exchange_key_fingerprint(public_key: &KeyExchangePublicKey) -> Fingerprint {
+    Fingerprint::derive(b"x25519", public_key.as_bytes())
+}
+
+// A 60-digit number (12 groups of 5) that two parties compare to confirm they hold each other's
+// keys. Each fingerprint contributes 30 digits and the halves are ordered by value, so both sides
+// compute the same number regardless of who is "ours" and who is "theirs".
+pub fn safety_number(ours: &Fingerprint, theirs: &Fingerprint) -> String {
+    let mut halves = [safety_number_digits(ours), safety_number_digits(theirs)];
+    halves.sort();
+    halves.concat().join(" ")
+}
+
+// Six 5-digit groups, each from 5 bytes of the fingerprint read as a big-endian integer
+fn safety_number_digits(fingerprint: &Fingerprint) -> Vec<String> {
+    fingerprint.bytes[..SAFETY_NUMBER_GROUPS_PER_KEY * 5]
+        .chunks(5)
+        .map(|chunk| {
+            let value = chunk.iter().fold(0u64, |acc, byte| (acc << 8) | *byte as u64);
+            format!("{:05}", value % 100_000)
+        })
+        .collect()
+}
+
+// Reads `count` (<= 32) bits starting at bit `start`, most significant bit first
+fn read_bits(bytes: &[u8], start: usize, count: usize) -> u32 {
+    (start..start + count).fold(0u32, |acc, bit| {
+        (acc << 1) | ((bytes[bit / 8] >> (7 - bit % 8)) & 1) as u32
+    })
+}
+
+// RFC 4648 base32 without padding
+fn base32_encode(bytes: &[u8]) -> String {
+    let bits = bytes.len() * 8;
+    (0..bits.div_ceil(5))
+        .map(|i| {
+            let start = i * 5;
+            let available = (bits - start).min(5);
+            let value = read_bits(bytes, start, available) << (5 - available);
+            BASE32_ALPHABET[value as usize] as char
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate_key_exchange_keypair, generate_signing_keypair};
+
+    #[test]
+    fn test_base32_rfc4648_vectors() {
+        assert_eq!(base32_encode(b""), "");
+        assert_eq!(base32_encode(b"f"), "MY");
+        assert_eq!(base32_encode(b"fo"), "MZXQ");
+        assert_eq!(base32_encode(b"foo"), "MZXW6");
+        assert_eq!(base32_encode(b"foob"), "MZXW6YQ");
+        assert_eq!(base32_encode(b"fooba"), "MZXW6YTB");
+        assert_eq!(base32_encode(b"foobar"), "MZXW6YTBOI");
+    }
+
+    #[test]
+    fn test_fingerprint_is_stable_and_key_specific() {
+        let (_, public_key) = generate_signing_keypair();
+        let (_, other_key) = generate_signing_keypair();
+        let fingerprint = signing_key_fingerprint(&public_key);
+
+        assert_eq!(fingerprint, signing_key_fingerprint(&public_key));
+        assert_ne!(fingerprint, signing_key_fingerprint(&other_key));
+        assert_eq!(fingerprint.version(), FINGERPRINT_VERSION);
+
+        // The same 32 bytes as an X25519 key get a different fingerprint
+        let as_exchange_key = KeyExchangePublicKey::from_bytes(*public_key.as_bytes());
+        assert_ne!(fingerprint, key_exchange_key_fingerprint(&as_exchange_key));
+    }
+
+    #[test]
+    fn test_fingerprint_derivation_pinned() {
+        // Pins the v1 derivation and renderings so ones users have already compared stay valid
+        let public_key = KeyExchangePublicKey::from_bytes([0x42; 32]);
+        let fingerprint = key_exchange_key_fingerprint(&public_key);
+        let mut hasher = Sha3_256::new();
+        hasher.update(b"paynless-fingerprint\x01x25519");
+        hasher.update([0x42; 32]);
+        let expected: [u8; 32] = hasher.finalize().into();
+        assert_eq!(fingerprint.as_bytes(), &expected);
+        assert_eq!(
+            hex::encode(fingerprint.as_bytes()),
+            "d4f2deaffc6634e3ca6e2a69bd651e86695dc50553c4651a04d2b0acea6ce712"
+        );
+        assert_eq!(fingerprint.to_base32_grouped(), "2TZN 5L74 MY2O HSTO FJU3 2ZI6 QZUV 3RIF");
+        assert_eq!(
+            fingerprint.to_safety_phrase(),
+            "stay notice program web globe impose claim shed have twenty element art"
+        );
+
+        // RFC 8032 test vector 1 and 2 public keys
+        let alice = SigningPublicKey::try_from_bytes(&hex_literal(
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        ))
+        .unwrap();
+        let bob = SigningPublicKey::try_from_bytes(&hex_literal(
+            "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+        ))
+        .unwrap();
+        assert_eq!(
+            safety_number(&signing_key_fingerprint(&alice), &signing_key_fingerprint(&bob)),
+            "74627 51786 22848 17850 04538 27604 75983 49248 83590 84229 00647 30909"
+        );
+    }
+
+    fn hex_literal(hex: &str) -> [u8; 32] {
+        hex::decode(hex).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_renderings() {
+        let (_, public_key) = generate_key_exchange_keypair();
+        let fingerprint = key_exchange_key_fingerprint(&public_key);
+
+        let base32 = fingerprint.to_base32_grouped();
+        let groups: Vec<&str> = base32.split(' ').collect();
+        assert_eq!(groups.len(), 8);
+        assert!(groups.iter().all(|g| g.len() == 4 && g.bytes().all(|c| BASE32_ALPHABET.contains(&c))));
+
+        let phrase = fingerprint.to_safety_phrase();
+        let words: Vec<&str> = phrase.split(' ').collect();
+        assert_eq!(words.len(), SAFETY_PHRASE_WORDS);
+        assert!(words.iter().all(|w| Language::English.find_word(w).is_some()));
+        assert_eq!(
+            Language::English.find_word(words[0]).unwrap() as u32,
+            (u32::from(fingerprint.as_bytes()[0]) << 3) | u32::from(fingerprint.as_bytes()[1] >> 5)
+        );
+    }
+
+    #[test]
+    fn test_safety_number_is_symmetric() {
+        let alice = signing_key_fingerprint(&generate_signing_keypair().1);
+        let bob = signing_key_fingerprint(&generate_signing_keypair().1);
+        let carol = signing_key_fingerprint(&generate_signing_keypair().1);
+
+        let number = safety_number(&alice, &bob);
+        assert_eq!(number, safety_number(&bob, &alice));
+        assert_ne!(number, safety_number(&alice, &carol));
+
+        let groups: Vec<&str> = number.split(' ').collect();
+        assert_eq!(groups.len(), 2 * SAFETY_NUMBER_GROUPS_PER_KEY);
+        assert!(groups.iter().all(|g| g.len() == 5 && g.bytes().all(|c| c.is_ascii_digit())));
+    }
+}
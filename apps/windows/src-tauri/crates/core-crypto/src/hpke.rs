@@ -0,0 +1,639 @@
+// --- Hybrid Public Key Encryption (RFC 9180) ---
+//
+// Implements the single HPKE ciphersuite that can be built from the primitives already in this
+// crate:
+//   KEM:  DHKEM(X25519, HKDF-SHA256)  (0x0020)
+//   KDF:  HKDF-SHA256                 (0x0001)
+//   AEAD: ChaCha20-Poly1305           (0x0003)
+// All four modes (base, psk, auth, auth_psk) and the secret export interface are supported, so
+// payloads interoperate with any other RFC 9180 implementation using the same suite.
+
+use crate::{
+    decrypt_symmetric, encrypt_symmetric, generate_key_exchange_keypair, key_exchange, CryptoError,
+    KeyExchangePublicKey, KeyExchangeSecretKey, Nonce, SymKey, KEY_EXCHANGE_PUBLIC_KEY_BYTES,
+    KEY_EXCHANGE_SECRET_KEY_BYTES, NONCE_BYTES, SYMMETRIC_KEY_BYTES,
+};
+use hkdf::Hkdf;
+use sha2::Sha256;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+pub const HPKE_KEM_ID: u16 = 0x0020;
+pub const HPKE_KDF_ID: u16 = 0x0001;
+pub const HPKE_AEAD_ID: u16 = 0x0003;
+
+// Length of the encapsulated key (the serialized ephemeral X25519 public key)
+pub const HPKE_ENC_BYTES: usize = KEY_EXCHANGE_PUBLIC_KEY_BYTES;
+// Minimum PSK length required by RFC 9180 section 5.1.2
+pub const HPKE_MIN_PSK_BYTES: usize = 32;
+
+const HASH_BYTES: usize = 32; // Nh for HKDF-SHA256
+const KEM_SUITE_ID: [u8; 5] = [b'K', b'E', b'M', 0x00, 0x20];
+const HPKE_SUITE_ID: [u8; 10] = [b'H', b'P', b'K', b'E', 0x00, 0x20, 0x00, 0x01, 0x00, 0x03];
+
+pub type Encapsulation = [u8; HPKE_ENC_BYTES];
+
+// Pre-shared key input for the psk and auth_psk modes
+#[derive(Debug, Clone, Copy)]
+pub struct PskInput<'a> {
+    pub psk: &'a [u8],
+    pub psk_id: &'a [u8],
+}
+
+// Mode selection on the sending side. The auth modes take the sender's static secret key.
+#[derive(Debug, Clone, Copy)]
+pub enum SenderMode<'a> {
+    Base,
+    Psk(PskInput<'a>),
+    Auth(&'a KeyExchangeSecretKey),
+    AuthPsk(&'a KeyExchangeSecretKey, PskInput<'a>),
+}
+
+// Mode selection on the receiving side. The auth modes take the sender's static public key.
+#[derive(Debug, Clone, Copy)]
+pub enum RecipientMode<'a> {
+    Base,
+    Psk(PskInput<'a>),
+    Auth(&'a KeyExchangePublicKey),
+    AuthPsk(&'a KeyExchangePublicKey, PskInput<'a>),
+}
+
+// Mode identifiers from RFC 9180 section 5
+fn mode_id(psk: bool, auth: bool) -> u8 {
+    match (psk, auth) {
+        (false, false) => 0x00,
+        (true, false) => 0x01,
+        (false, true) => 0x02,
+        (true, true) => 0x03,
+    }
+}
+
+fn labeled_extract(
+    suite_id: &[u8],
+    salt: &[u8],
+    label: &[u8],
+    ikm: &[u8],
+) -> Zeroizing<[u8; HASH_BYTES]> {
+    let mut labeled_ikm = Zeroizing::new(Vec::with_capacity(7 + suite_id.len() + label.len() + ikm.len()));
+    labeled_ikm.extend_from_slice(b"HPKE-v1");
+    labeled_ikm.extend_from_slice(suite_id);
+    labeled_ikm.extend_from_slice(label);
+    labeled_ikm.extend_from_slice(ikm);
+    let (prk, _) = Hkdf::<Sha256>::extract(Some(salt), &labeled_ikm);
+    Zeroizing::new(prk.into())
+}
+
+fn labeled_expand(
+    suite_id: &[u8],
+    prk: &[u8],
+    label: &[u8],
+    info: &[u8],
+    output: &mut [u8],
+) -> Result<(), CryptoError> {
+    let length = u16::try_from(output.len())
+        .map_err(|_| CryptoError::HpkeError("requested output too long".to_string()))?;
+    let mut labeled_info = Vec::with_capacity(2 + 7 + suite_id.len() + label.len() + info.len());
+    labeled_info.extend_from_slice(&length.to_be_bytes());
+    labeled_info.extend_from_slice(b"HPKE-v1");
+    labeled_info.extend_from_slice(suite_id);
+    labeled_info.extend_from_slice(label);
+    labeled_info.extend_from_slice(info);
+
+    let hk = Hkdf::<Sha256>::from_prk(prk)
+        .map_err(|e| CryptoError::KeyDerivationError(format!("Invalid HPKE PRK: {}", e)))?;
+    hk.expand(&labeled_info, output)
+        .map_err(|e| CryptoError::HpkeError(format!("HKDF expansion failed: {}", e)))
+}
+
+/// Deterministically derives an X25519 key pair from input keying material (RFC 9180 `DeriveKeyPair`).
+/// `ikm` should carry at least 32 bytes of entropy.
+pub fn derive_key_pair(ikm: &[u8]) -> Result<(KeyExchangeSecretKey, KeyExchangePublicKey), CryptoError> {
+    let dkp_prk = labeled_extract(&KEM_SUITE_ID, b"", b"dkp_prk", ikm);
+    let mut secret_key = KeyExchangeSecretKey::from_bytes([0u8; KEY_EXCHANGE_SECRET_KEY_BYTES]);
+    labeled_expand(&KEM_SUITE_ID, &*dkp_prk, b"sk", b"", &mut secret_key.0)?;
+    let public_key = secret_key.public_key();
+    Ok((secret_key, public_key))
+}
+
+// DHKEM ExtractAndExpand
+fn extract_and_expand(
+    dh: &[u8],
+    kem_context: &[u8],
+) -> Result<Zeroizing<[u8; HASH_BYTES]>, CryptoError> {
+    let eae_prk = labeled_extract(&KEM_SUITE_ID, b"", b"eae_prk", dh);
+    let mut shared_secret = Zeroizing::new([0u8; HASH_BYTES]);
+    labeled_expand(&KEM_SUITE_ID, &*eae_prk, b"shared_secret", kem_context, shared_secret.as_mut())?;
+    Ok(shared_secret)
+}
+
+// Encap / AuthEncap with an explicit ephemeral key (random in production, fixed in test vectors)
+fn encap(
+    recipient: &KeyExchangePublicKey,
+    sender: Option<&KeyExchangeSecretKey>,
+    ephemeral: (&KeyExchangeSecretKey, &KeyExchangePublicKey),
+) -> Result<(Zeroizing<[u8; HASH_BYTES]>, Encapsulation), CryptoError> {
+    let (ephemeral_secret, ephemeral_public) = ephemeral;
+    let mut dh = Zeroizing::new(key_exchange(ephemeral_secret, recipient)?.expose_secret().to_vec());
+    let mut kem_context = Vec::with_capacity(3 * KEY_EXCHANGE_PUBLIC_KEY_BYTES);
+    kem_context.extend_from_slice(ephemeral_public.as_bytes());
+    kem_context.extend_from_slice(recipient.as_bytes());
+
+    if let Some(sender_secret) = sender {
+        dh.extend_from_slice(key_exchange(sender_secret, recipient)?.expose_secret());
+        kem_context.extend_from_slice(sender_secret.public_key().as_bytes());
+    }
+
+    let shared_secret = extract_and_expand(&dh, &kem_context)?;
+    Ok((shared_secret, *ephemeral_public.as_bytes()))
+}
+
+// Decap / AuthDecap
+fn decap(
+    enc: &Encapsulation,
+    recipient_secret: &KeyExchangeSecretKey,
+    sender: Option<&KeyExchangePublicKey>,
+) -> Result<Zeroizing<[u8; HASH_BYTES]>, CryptoError> {
+    let ephemeral_public = KeyExchangePublicKey::from_bytes(*enc);
+    let recipient_public = recipient_secret.public_key();
+
+    let mut dh = Zeroizing::new(key_exchange(recipient_secret, &ephemeral_public)?.expose_secret().to_vec());
+    let mut kem_context = Vec::with_capacity(3 * KEY_EXCHANGE_PUBLIC_KEY_BYTES);
+    kem_context.extend_from_slice(enc);
+    kem_context.extend_from_slice(recipient_public.as_bytes());
+
+    if let Some(sender_public) = sender {
+        dh.extend_from_slice(key_exchange(recipient_secret, sender_public)?.expose_secret());
+        kem_context.extend_from_slice(sender_public.as_bytes());
+    }
+
+    extract_and_expand(&dh, &kem_context)
+}
+
+// Shared state derived by KeySchedule; wrapped by the sender and recipient contexts
+#[derive(Zeroize, ZeroizeOnDrop)]
+struct KeySchedule {
+    key: SymKey,
+    base_nonce: Nonce,
+    exporter_secret: [u8; HASH_BYTES],
+    seq: u64,
+}
+
+impl KeySchedule {
+    fn new(
+        shared_secret: &[u8],
+        info: &[u8],
+        psk: Option<PskInput<'_>>,
+        auth: bool,
+    ) -> Result<Self, CryptoError> {
+        // VerifyPSKInputs: the mode already determines whether a PSK is present
+        let (psk_bytes, psk_id) = match psk {
+            Some(input) => {
+                if input.psk.len() < HPKE_MIN_PSK_BYTES {
+                    return Err(CryptoError::HpkeError(format!(
+                        "PSK must be at least {} bytes",
+                        HPKE_MIN_PSK_BYTES
+                    )));
+                }
+                if input.psk_id.is_empty() {
+                    return Err(CryptoError::HpkeError("PSK id must not be empty".to_string()));
+                }
+                (input.psk, input.psk_id)
+            }
+            None => (&[][..], &[][..]),
+        };
+        let mode = mode_id(psk.is_some(), auth);
+
+        let psk_id_hash = labeled_extract(&HPKE_SUITE_ID, b"", b"psk_id_hash", psk_id);
+        let info_hash = labeled_extract(&HPKE_SUITE_ID, b"", b"info_hash", info);
+        let mut key_schedule_context = Vec::with_capacity(1 + 2 * HASH_BYTES);
+        key_schedule_context.push(mode);
+        key_schedule_context.extend_from_slice(&*psk_id_hash);
+        key_schedule_context.extend_from_slice(&*info_hash);
+
+        let secret = labeled_extract(&HPKE_SUITE_ID, shared_secret, b"secret", psk_bytes);
+
+        let mut key = SymKey::from_bytes([0u8; SYMMETRIC_KEY_BYTES]);
+        labeled_expand(&HPKE_SUITE_ID, &*secret, b"key", &key_schedule_context, &mut key.0)?;
+        let mut base_nonce = [0u8; NONCE_BYTES];
+        labeled_expand(&HPKE_SUITE_ID, &*secret, b"base_nonce", &key_schedule_context, &mut base_nonce)?;
+        let mut exporter_secret = [0u8; HASH_BYTES];
+        labeled_expand(&HPKE_SUITE_ID, &*secret, b"exp", &key_schedule_context, &mut exporter_secret)?;
+
+        Ok(KeySchedule {
+            key,
+            base_nonce,
+            exporter_secret,
+            seq: 0,
+        })
+    }
+
+    // ComputeNonce: base_nonce XOR I2OSP(seq, Nn)
+    fn current_nonce(&self) -> Nonce {
+        let mut nonce = self.base_nonce;
+        for (n, s) in nonce[NONCE_BYTES - 8..].iter_mut().zip(self.seq.to_be_bytes()) {
+            *n ^= s;
+        }
+        nonce
+    }
+
+    fn increment_seq(&mut self) -> Result<(), CryptoError> {
+        self.seq = self
+            .seq
+            .checked_add(1)
+            .ok_or_else(|| CryptoError::HpkeError("message limit reached".to_string()))?;
+        Ok(())
+    }
+
+    fn export(&self, exporter_context: &[u8], length: usize) -> Result<Vec<u8>, CryptoError> {
+        if length > 255 * HASH_BYTES {
+            return Err(CryptoError::HpkeError("export length too large".to_string()));
+        }
+        let mut output = vec![0u8; length];
+        labeled_expand(&HPKE_SUITE_ID, &self.exporter_secret, b"sec", exporter_context, &mut output)?;
+        Ok(output)
+    }
+}
+
+// Encryption context held by the sender after setup
+pub struct SenderContext(KeySchedule);
+
+impl SenderContext {
+    /// Encrypts the next message in sequence. Messages must be opened in the same order.
+    pub fn seal(&mut self, aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        let ciphertext = encrypt_symmetric(&self.0.key, plaintext, &self.0.current_nonce(), Some(aad))?;
+        self.0.increment_seq()?;
+        Ok(ciphertext)
+    }
+
+    /// Derives an exported secret bound to this context (RFC 9180 section 5.3).
+    pub fn export(&self, exporter_context: &[u8], length: usize) -> Result<Vec<u8>, CryptoError> {
+        self.0.export(exporter_context, length)
+    }
+}
+
+// Decryption context held by the recipient after setup
+pub struct RecipientContext(KeySchedule);
+
+impl RecipientContext {
+    /// Decrypts the next message in sequence.
+    /// The sequence number only advances on success, so a forged message doesn't desynchronize the context.
+    pub fn open(&mut self, aad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        let plaintext = decrypt_symmetric(&self.0.key, ciphertext, &self.0.current_nonce(), Some(aad))?;
+        self.0.increment_seq()?;
+        Ok(plaintext)
+    }
+
+    /// Derives an exported secret bound to this context (RFC 9180 section 5.3).
+    pub fn export(&self, exporter_context: &[u8], length: usize) -> Result<Vec<u8>, CryptoError> {
+        self.0.export(exporter_context, length)
+    }
+}
+
+fn setup_sender_with_ephemeral(
+    mode: SenderMode<'_>,
+    recipient: &KeyExchangePublicKey,
+    info: &[u8],
+    ephemeral: (&KeyExchangeSecretKey, &KeyExchangePublicKey),
+) -> Result<(Encapsulation, SenderContext), CryptoError> {
+    let (sender, psk) = match mode {
+        SenderMode::Base => (None, None),
+        SenderMode::Psk(psk) => (None, Some(psk)),
+        SenderMode::Auth(sender) => (Some(sender), None),
+        SenderMode::AuthPsk(sender, psk) => (Some(sender), Some(psk)),
+    };
+    let (shared_secret, enc) = encap(recipient, sender, ephemeral)?;
+    let schedule = KeySchedule::new(&*shared_secret, info, psk, sender.is_some())?;
+    Ok((enc, SenderContext(schedule)))
+}
+
+/// Sets up an HPKE sender context for `recipient` (RFC 9180 `SetupBaseS`, `SetupPSKS`,
+/// `SetupAuthS` and `SetupAuthPSKS`, depending on `mode`).
+///
+/// # Returns
+/// * `Ok((enc, context))` where `enc` must be sent to the recipient alongside the ciphertexts.
+/// * `Err(CryptoError::KeyExchangeError)` if a public key is low-order.
+/// * `Err(CryptoError::HpkeError)` if the PSK inputs are invalid.
+pub fn setup_sender(
+    mode: SenderMode<'_>,
+    recipient: &KeyExchangePublicKey,
+    info: &[u8],
+) -> Result<(Encapsulation, SenderContext), CryptoError> {
+    let (ephemeral_secret, ephemeral_public) = generate_key_exchange_keypair();
+    setup_sender_with_ephemeral(mode, recipient, info, (&ephemeral_secret, &ephemeral_public))
+}
+
+/// Sets up an HPKE recipient context from the sender's `enc` (RFC 9180 `SetupBaseR`,
+/// `SetupPSKR`, `SetupAuthR` and `SetupAuthPSKR`, depending on `mode`).
+pub fn setup_recipient(
+    mode: RecipientMode<'_>,
+    enc: &Encapsulation,
+    recipient_secret: &KeyExchangeSecretKey,
+    info: &[u8],
+) -> Result<RecipientContext, CryptoError> {
+    let (sender, psk) = match mode {
+        RecipientMode::Base => (None, None),
+        RecipientMode::Psk(psk) => (None, Some(psk)),
+        RecipientMode::Auth(sender) => (Some(sender), None),
+        RecipientMode::AuthPsk(sender, psk) => (Some(sender), Some(psk)),
+    };
+    let shared_secret = decap(enc, recipient_secret, sender)?;
+    let schedule = KeySchedule::new(&*shared_secret, info, psk, sender.is_some())?;
+    Ok(RecipientContext(schedule))
+}
+
+/// Single-shot encryption: sets up a sender context and seals one message.
+pub fn seal(
+    mode: SenderMode<'_>,
+    recipient: &KeyExchangePublicKey,
+    info: &[u8],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> Result<(Encapsulation, Vec<u8>), CryptoError> {
+    let (enc, mut context) = setup_sender(mode, recipient, info)?;
+    let ciphertext = context.seal(aad, plaintext)?;
+    Ok((enc, ciphertext))
+}
+
+/// Single-shot decryption of a message produced by `seal`.
+pub fn open(
+    mode: RecipientMode<'_>,
+    enc: &Encapsulation,
+    recipient_secret: &KeyExchangeSecretKey,
+    info: &[u8],
+    aad: &[u8],
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, CryptoError> {
+    let mut context = setup_recipient(mode, enc, recipient_secret, info)?;
+    context.open(aad, ciphertext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unhex(s: &str) -> Vec<u8> {
+        hex::decode(s).expect("valid hex in test vector")
+    }
+
+    struct Encryption {
+        seq: u64,
+        ct: &'static str,
+    }
+
+    struct Export {
+        context: &'static str,
+        value: &'static str,
+    }
+
+    // One RFC 9180 Appendix A.2 test vector (DHKEM(X25519, HKDF-SHA256), HKDF-SHA256, ChaCha20-Poly1305)
+    struct TestVector {
+        ikm_e: &'static str,
+        pk_em: &'static str,
+        ikm_r: &'static str,
+        pk_rm: &'static str,
+        ikm_s: Option<&'static str>,
+        pk_sm: Option<&'static str>,
+        psk: bool,
+        enc: &'static str,
+        encryptions: &'static [Encryption],
+        exports: &'static [Export],
+    }
+
+    // Inputs shared by every A.2 vector
+    // "Ode on a Grecian Urn"
+    const INFO: &str = "4f6465206f6e2061204772656369616e2055726e";
+    // "Beauty is truth, truth beauty"
+    const PLAINTEXT: &str = "4265617574792069732074727574682c20747275746820626561757479";
+    const PSK: &str = "0247fd33b913760fa1fa51e1892d9f307fbe65eb171e8132c2af18555a738b82";
+    // "Ennyn Durin aran Moria"
+    const PSK_ID: &str = "456e6e796e20447572696e206172616e204d6f726961";
+
+    fn run_vector(vector: &TestVector) {
+        let info = unhex(INFO);
+        let plaintext = unhex(PLAINTEXT);
+        let (sk_e, pk_e) = derive_key_pair(&unhex(vector.ikm_e)).unwrap();
+        let (sk_r, pk_r) = derive_key_pair(&unhex(vector.ikm_r)).unwrap();
+        assert_eq!(hex::encode(pk_e.as_bytes()), vector.pk_em);
+        assert_eq!(hex::encode(pk_r.as_bytes()), vector.pk_rm);
+
+        let sender_keys = vector.ikm_s.map(|ikm| derive_key_pair(&unhex(ikm)).unwrap());
+        if let Some((_, pk_s)) = &sender_keys {
+            assert_eq!(Some(hex::encode(pk_s.as_bytes()).as_str()), vector.pk_sm);
+        }
+        let (psk_bytes, psk_id_bytes) = (unhex(PSK), unhex(PSK_ID));
+        let psk = vector.psk.then_some(PskInput { psk: &psk_bytes, psk_id: &psk_id_bytes });
+
+        let sender_mode = match (&sender_keys, psk) {
+            (None, None) => SenderMode::Base,
+            (None, Some(psk)) => SenderMode::Psk(psk),
+            (Some((sk_s, _)), None) => SenderMode::Auth(sk_s),
+            (Some((sk_s, _)), Some(psk)) => SenderMode::AuthPsk(sk_s, psk),
+        };
+        let recipient_mode = match (&sender_keys, psk) {
+            (None, None) => RecipientMode::Base,
+            (None, Some(psk)) => RecipientMode::Psk(psk),
+            (Some((_, pk_s)), None) => RecipientMode::Auth(pk_s),
+            (Some((_, pk_s)), Some(psk)) => RecipientMode::AuthPsk(pk_s, psk),
+        };
+
+        let (enc, mut sender) = setup_sender_with_ephemeral(sender_mode, &pk_r, &info, (&sk_e, &pk_e)).unwrap();
+        assert_eq!(hex::encode(enc), vector.enc);
+        let mut recipient = setup_recipient(recipient_mode, &enc, &sk_r, &info).unwrap();
+
+        // The vectors use aad = "Count-<seq>"; messages in between are sealed and opened to advance both sides
+        let mut seq = 0;
+        for encryption in vector.encryptions {
+            while seq <= encryption.seq {
+                let aad = format!("Count-{}", seq);
+                let ciphertext = sender.seal(aad.as_bytes(), &plaintext).unwrap();
+                if seq == encryption.seq {
+                    assert_eq!(hex::encode(&ciphertext), encryption.ct, "sequence number {}", seq);
+                }
+                assert_eq!(recipient.open(aad.as_bytes(), &ciphertext).unwrap(), plaintext);
+                seq += 1;
+            }
+        }
+
+        for export in vector.exports {
+            let context = unhex(export.context);
+            assert_eq!(hex::encode(sender.export(&context, 32).unwrap()), export.value);
+            assert_eq!(hex::encode(recipient.export(&context, 32).unwrap()), export.value);
+        }
+    }
+
+    // RFC 9180 A.2.1
+    #[test]
+    fn test_rfc9180_base_mode_vector() {
+        run_vector(&TestVector {
+            ikm_e: "909a9b35d3dc4713a5e72a4da274b55d3d3821a37e5d099e74a647db583a904b",
+            pk_em: "1afa08d3dec047a643885163f1180476fa7ddb54c6a8029ea33f95796bf2ac4a",
+            ikm_r: "1ac01f181fdf9f352797655161c58b75c656a6cc2716dcb66372da835542e1df",
+            pk_rm: "4310ee97d88cc1f088a5576c77ab0cf5c3ac797f3d95139c6c84b5429c59662a",
+            ikm_s: None,
+            pk_sm: None,
+            psk: false,
+            enc: "1afa08d3dec047a643885163f1180476fa7ddb54c6a8029ea33f95796bf2ac4a",
+            encryptions: &[
+                Encryption { seq: 0, ct: "1c5250d8034ec2b784ba2cfd69dbdb8af406cfe3ff938e131f0def8c8b60b4db21993c62ce81883d2dd1b51a28" },
+                Encryption { seq: 1, ct: "6b53c051e4199c518de79594e1c4ab18b96f081549d45ce015be002090bb119e85285337cc95ba5f59992dc98c" },
+                Encryption { seq: 2, ct: "71146bd6795ccc9c49ce25dda112a48f202ad220559502cef1f34271e0cb4b02b4f10ecac6f48c32f878fae86b" },
+                Encryption { seq: 4, ct: "63357a2aa291f5a4e5f27db6baa2af8cf77427c7c1a909e0b37214dd47db122bb153495ff0b02e9e54a50dbe16" },
+                Encryption { seq: 255, ct: "18ab939d63ddec9f6ac2b60d61d36a7375d2070c9b683861110757062c52b8880a5f6b3936da9cd6c23ef2a95c" },
+                Encryption { seq: 256, ct: "7a4a13e9ef23978e2c520fd4d2e757514ae160cd0cd05e556ef692370ca53076214c0c40d4c728d6ed9e727a5b" },
+            ],
+            exports: &[
+                Export { context: "", value: "4bbd6243b8bb54cec311fac9df81841b6fd61f56538a775e7c80a9f40160606e" },
+                Export { context: "00", value: "8c1df14732580e5501b00f82b10a1647b40713191b7c1240ac80e2b68808ba69" },
+                Export { context: "54657374436f6e74657874", value: "5acb09211139c43b3090489a9da433e8a30ee7188ba8b0a9a1ccf0c229283e53" },
+            ],
+        });
+    }
+
+    // RFC 9180 A.2.2
+    #[test]
+    fn test_rfc9180_psk_mode_vector() {
+        run_vector(&TestVector {
+            ikm_e: "35706a0b09fb26fb45c39c2f5079c709c7cf98e43afa973f14d88ece7e29c2e3",
+            pk_em: "2261299c3f40a9afc133b969a97f05e95be2c514e54f3de26cbe5644ac735b04",
+            ikm_r: "26b923eade72941c8a85b09986cdfa3f1296852261adedc52d58d2930269812b",
+            pk_rm: "13640af826b722fc04feaa4de2f28fbd5ecc03623b317834e7ff4120dbe73062",
+            ikm_s: None,
+            pk_sm: None,
+            psk: true,
+            enc: "2261299c3f40a9afc133b969a97f05e95be2c514e54f3de26cbe5644ac735b04",
+            encryptions: &[
+                Encryption { seq: 0, ct: "4a177f9c0d6f15cfdf533fb65bf84aecdc6ab16b8b85b4cf65a370e07fc1d78d28fb073214525276f4a89608ff" },
+            ],
+            exports: &[],
+        });
+    }
+
+    // RFC 9180 A.2.3
+    #[test]
+    fn test_rfc9180_auth_mode_vector() {
+        run_vector(&TestVector {
+            ikm_e: "938d3daa5a8904540bc24f48ae90eed3f4f7f11839560597b55e7c9598c996c0",
+            pk_em: "f7674cc8cd7baa5872d1f33dbaffe3314239f6197ddf5ded1746760bfc847e0e",
+            ikm_r: "64835d5ee64aa7aad57c6f2e4f758f7696617f8829e70bc9ac7a5ef95d1c756c",
+            pk_rm: "1a478716d63cb2e16786ee93004486dc151e988b34b475043d3e0175bdb01c44",
+            ikm_s: Some("9d8f94537d5a3ddef71234c0baedfad4ca6861634d0b94c3007fed557ad17df6"),
+            pk_sm: Some("f0f4f9e96c54aeed3f323de8534fffd7e0577e4ce269896716bcb95643c8712b"),
+            psk: false,
+            enc: "f7674cc8cd7baa5872d1f33dbaffe3314239f6197ddf5ded1746760bfc847e0e",
+            encryptions: &[
+                Encryption { seq: 0, ct: "ab1a13c9d4f01a87ec3440dbd756e2677bd2ecf9df0ce7ed73869b98e00c09be111cb9fdf077347aeb88e61bdf" },
+            ],
+            exports: &[],
+        });
+    }
+
+    // RFC 9180 A.2.4
+    #[test]
+    fn test_rfc9180_auth_psk_mode_vector() {
+        run_vector(&TestVector {
+            ikm_e: "49d6eac8c6c558c953a0a252929a818745bb08cd3d29e15f9f5db5eb2e7d4b84",
+            pk_em: "656a2e00dc9990fd189e6e473459392df556e9a2758754a09db3f51179a3fc02",
+            ikm_r: "f3304ddcf15848488271f12b75ecaf72301faabf6ad283654a14c398832eb184",
+            pk_rm: "a5099431c35c491ec62ca91df1525d6349cb8aa170c51f9581f8627be6334851",
+            ikm_s: Some("20ade1d5203de1aadfb261c4700b6432e260d0d317be6ebbb8d7fffb1f86ad9d"),
+            pk_sm: Some("3ac5bd4dd66ff9f2740bef0d6ccb66daa77bff7849d7895182b07fb74d087c45"),
+            psk: true,
+            enc: "656a2e00dc9990fd189e6e473459392df556e9a2758754a09db3f51179a3fc02",
+            encryptions: &[
+                Encryption { seq: 0, ct: "9aa52e29274fc6172e38a4461361d2342585d3aeec67fb3b721ecd63f059577c7fe886be0ede01456ebc67d597" },
+            ],
+            exports: &[],
+        });
+    }
+
+    #[test]
+    fn test_single_shot_roundtrip_all_modes() {
+        let (sk_r, pk_r) = generate_key_exchange_keypair();
+        let (sk_s, pk_s) = generate_key_exchange_keypair();
+        let psk = PskInput { psk: &[7u8; 32], psk_id: b"shared-psk" };
+
+        let modes = [
+            (SenderMode::Base, RecipientMode::Base),
+            (SenderMode::Psk(psk), RecipientMode::Psk(psk)),
+            (SenderMode::Auth(&sk_s), RecipientMode::Auth(&pk_s)),
+            (SenderMode::AuthPsk(&sk_s, psk), RecipientMode::AuthPsk(&pk_s, psk)),
+        ];
+        for (sender_mode, recipient_mode) in modes {
+            let (enc, ciphertext) = seal(sender_mode, &pk_r, b"app info", b"aad", b"hello hpke").unwrap();
+            let plaintext = open(recipient_mode, &enc, &sk_r, b"app info", b"aad", &ciphertext).unwrap();
+            assert_eq!(plaintext, b"hello hpke");
+
+            // Wrong info or aad must fail
+            assert_eq!(
+                open(recipient_mode, &enc, &sk_r, b"other info", b"aad", &ciphertext).unwrap_err(),
+                CryptoError::DecryptionError
+            );
+            assert_eq!(
+                open(recipient_mode, &enc, &sk_r, b"app info", b"other", &ciphertext).unwrap_err(),
+                CryptoError::DecryptionError
+            );
+        }
+    }
+
+    #[test]
+    fn test_mode_mismatch_fails() {
+        let (sk_r, pk_r) = generate_key_exchange_keypair();
+        let (sk_s, _) = generate_key_exchange_keypair();
+        let (_, impostor_pk) = generate_key_exchange_keypair();
+        let psk = PskInput { psk: &[7u8; 32], psk_id: b"shared-psk" };
+        let other_psk = PskInput { psk: &[8u8; 32], psk_id: b"shared-psk" };
+
+        // Authenticated by a different sender than the recipient expects
+        let (enc, ct) = seal(SenderMode::Auth(&sk_s), &pk_r, b"", b"", b"msg").unwrap();
+        assert_eq!(
+            open(RecipientMode::Auth(&impostor_pk), &enc, &sk_r, b"", b"", &ct).unwrap_err(),
+            CryptoError::DecryptionError
+        );
+        // Opened in base mode
+        assert_eq!(open(RecipientMode::Base, &enc, &sk_r, b"", b"", &ct).unwrap_err(), CryptoError::DecryptionError);
+
+        // Wrong PSK
+        let (enc, ct) = seal(SenderMode::Psk(psk), &pk_r, b"", b"", b"msg").unwrap();
+        assert_eq!(
+            open(RecipientMode::Psk(other_psk), &enc, &sk_r, b"", b"", &ct).unwrap_err(),
+            CryptoError::DecryptionError
+        );
+    }
+
+    #[test]
+    fn test_psk_validation() {
+        let (_, pk_r) = generate_key_exchange_keypair();
+        let short = PskInput { psk: &[1u8; 16], psk_id: b"id" };
+        let no_id = PskInput { psk: &[1u8; 32], psk_id: b"" };
+
+        for psk in [short, no_id] {
+            match setup_sender(SenderMode::Psk(psk), &pk_r, b"") {
+                Err(CryptoError::HpkeError(_)) => {}
+                Err(e) => panic!("Expected HpkeError, got {:?}", e),
+                Ok(_) => panic!("Expected HpkeError, got a context"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_out_of_order_open_fails_without_desync() {
+        let (sk_r, pk_r) = generate_key_exchange_keypair();
+        let (enc, mut sender) = setup_sender(SenderMode::Base, &pk_r, b"").unwrap();
+        let mut recipient = setup_recipient(RecipientMode::Base, &enc, &sk_r, b"").unwrap();
+
+        let first = sender.seal(b"", b"first").unwrap();
+        let second = sender.seal(b"", b"second").unwrap();
+
+        // Opening the second message first fails and must not advance the recipient
+        assert_eq!(recipient.open(b"", &second).unwrap_err(), CryptoError::DecryptionError);
+        assert_eq!(recipient.open(b"", &first).unwrap(), b"first");
+        assert_eq!(recipient.open(b"", &second).unwrap(), b"second");
+    }
+
+    #[test]
+    fn test_export_length_limit() {
+        let (_, pk_r) = generate_key_exchange_keypair();
+        let (_, sender) = setup_sender(SenderMode::Base, &pk_r, b"").unwrap();
+        assert_eq!(sender.export(b"ctx", 255 * HASH_BYTES).unwrap().len(), 255 * HASH_BYTES);
+        assert!(matches!(sender.export(b"ctx", 255 * HASH_BYTES + 1), Err(CryptoError::HpkeError(_))));
+    }
+}
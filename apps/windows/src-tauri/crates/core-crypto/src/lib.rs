@@ -1,6 +1,7 @@
 use chacha20poly1305::{
-    aead::{Aead, AeadCore, KeyInit, OsRng},
+    aead::{Aead, AeadCore, KeyInit, OsRng, Payload},
     ChaCha20Poly1305, Key, Nonce as CryptoNonce, // Alias CryptoNonce to avoid clash
+    XChaCha20Poly1305, XNonce as CryptoXNonce,
 };
 use thiserror::Error;
 use sha3::{Digest, Sha3_256};
@@ -8,6 +9,139 @@ use signature::Error as SignatureTraitError;
 // Separate bip39 imports
 use bip39::Mnemonic;
 use bip39::Language;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+pub use subtle::ConstantTimeEq;
+use subtle::Choice;
+
+mod stream;
+pub use stream::{
+    decrypt_stream, encrypt_stream, stream_ciphertext_len, STREAM_CHUNK_BYTES,
+    STREAM_NONCE_PREFIX_BYTES, STREAM_TAG_BYTES,
+};
+
+mod envelope;
+pub use envelope::{
+    open_envelope, reseal_envelope, seal_envelope, symmetric_key_id, Envelope, KeyId,
+    SymmetricAlgorithm, ENVELOPE_MAGIC, ENVELOPE_VERSION, KEY_ID_BYTES,
+};
+
+mod signing_context;
+pub use signing_context::{sign_with_context, verify_with_context, SigningContext};
+
+mod prehash;
+pub use prehash::{sign_prehashed, verify_prehashed, Ed25519phHasher};
+
+mod multisig;
+pub use multisig::{
+    verify_policy, MultisigPolicy, SignatureCollector, MULTISIG_MAX_KEYS, MULTISIG_POLICY_MAGIC,
+    MULTISIG_POLICY_VERSION,
+};
+
+mod minisign;
+pub use minisign::{
+    minisign_sign, minisign_verify, MinisignKeyId, MinisignPublicKey, MinisignSecretKey,
+    MinisignSignature, MINISIGN_KEY_ID_BYTES, MINISIGN_MAX_LEGACY_INPUT_BYTES,
+};
+
+mod token;
+pub use token::{
+    attenuate_token, issue_token, verify_token, KeyToken, TokenClaims, TokenRights,
+    KEY_TOKEN_MAGIC, KEY_TOKEN_MAX_CHAIN_LENGTH, KEY_TOKEN_MAX_CONTENT_ID_BYTES,
+    KEY_TOKEN_NONCE_BYTES, KEY_TOKEN_VERSION,
+};
+
+mod fingerprint;
+pub use fingerprint::{
+    key_exchange_key_fingerprint, safety_number, signing_key_fingerprint, Fingerprint,
+    FINGERPRINT_BYTES, FINGERPRINT_VERSION, SAFETY_PHRASE_WORDS,
+};
+
+mod derivation_path;
+pub use derivation_path::{derive_path, DerivationPath, DerivedKey, DerivedKeyKind, PathSegment};
+
+mod slip10;
+pub use slip10::{
+    derive_identity_keypair, slip10_derive_keypair, IdentityScheme, Slip10ExtendedKey, Slip10Path,
+    SLIP10_CHAIN_CODE_BYTES, SLIP10_HARDENED_OFFSET,
+};
+
+mod content_epoch;
+pub use content_epoch::{
+    decrypt_content, encrypt_content, rotate_content, ContentCiphertext, EpochWindow,
+    CONTENT_CIPHERTEXT_MAGIC, CONTENT_CIPHERTEXT_VERSION,
+};
+
+#[cfg(feature = "serde")]
+mod serde_support;
+#[cfg(feature = "serde")]
+pub use serde_support::{SerdeSecret, SerializableSecret};
+
+mod key_wrap;
+pub use key_wrap::{
+    unwrap_key, wrap_key, KeyWrapAlgorithm, WrappableKey, WrappedKey, WrappedKeyKind,
+    KEY_WRAP_OVERHEAD_BYTES, WRAPPED_KEY_MAGIC, WRAPPED_KEY_VERSION,
+};
+
+pub mod sealed_box;
+pub mod hpke;
+
+mod password;
+pub use password::{
+    calibrate_argon2_params, derive_key_from_password, hash_password, verify_password,
+    Argon2Params, ARGON2_MAX_ITERATIONS, ARGON2_MAX_MEMORY_KIB, ARGON2_MAX_PARALLELISM,
+    ARGON2_PARAMS_BYTES, ARGON2_PARAMS_VERSION, ARGON2_SALT_BYTES,
+};
+
+// --- Secret Key Material ---
+
+// Declares a fixed-size secret newtype. The bytes are wiped when the value is dropped, `Debug`
+// prints a placeholder instead of key material, and there is deliberately no `Copy` or `Clone`:
+// reading the bytes always goes through an explicit `expose_secret()` call. Equality is
+// constant-time (`==` is implemented in terms of `ct_eq`).
+macro_rules! secret_bytes {
+    ($(#[$attr:meta])* pub struct $name:ident([u8; $len:expr]);) => {
+        $(#[$attr])*
+        #[derive(Zeroize, ZeroizeOnDrop)]
+        pub struct $name([u8; $len]);
+
+        impl $name {
+            // Takes ownership of the bytes; the caller's copy is not wiped
+            pub fn from_bytes(bytes: [u8; $len]) -> Self {
+                Self(bytes)
+            }
+
+            pub fn expose_secret(&self) -> &[u8; $len] {
+                &self.0
+            }
+        }
+
+        impl std::fmt::Debug for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(concat!(stringify!($name), "([REDACTED])"))
+            }
+        }
+
+        impl ConstantTimeEq for $name {
+            fn ct_eq(&self, other: &Self) -> Choice {
+                self.0.ct_eq(&other.0)
+            }
+        }
+
+        impl PartialEq for $name {
+            fn eq(&self, other: &Self) -> bool {
+                self.ct_eq(other).into()
+            }
+        }
+
+        impl Eq for $name {}
+    };
+}
+
+// Compares two byte strings (MACs, tags, key hashes) without short-circuiting on the first
+// differing byte. Only the lengths are compared in variable time.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
+}
 
 // Define a type alias for our standard hash output (32 bytes for SHA3-256)
 pub type Hash = [u8; 32];
@@ -25,8 +159,12 @@ pub fn hash_data(data: &[u8]) -> Hash {
 pub const SYMMETRIC_KEY_BYTES: usize = 32;
 pub const NONCE_BYTES: usize = 12; // Standard 96-bit nonce for ChaCha20Poly1305
 
-// Type aliases for clarity
-pub type SymKey = [u8; SYMMETRIC_KEY_BYTES];
+secret_bytes! {
+    // ChaCha20-Poly1305 / XChaCha20-Poly1305 key
+    pub struct SymKey([u8; SYMMETRIC_KEY_BYTES]);
+}
+
+// Type alias for clarity
 pub type Nonce = [u8; NONCE_BYTES];
 
 // Custom Error type for cryptographic operations
@@ -54,6 +192,70 @@ pub enum CryptoError {
     MnemonicValidationError(String),
     #[error("Failed to convert mnemonic to seed: {0}")]
     MnemonicToSeedError(String),
+    #[error("I/O error during streaming operation: {0}")]
+    IoError(String),
+    #[error("Not a ciphertext envelope (invalid magic bytes)")]
+    InvalidEnvelopeMagic,
+    #[error("Unsupported envelope version: {0}")]
+    UnsupportedEnvelopeVersion(u8),
+    #[error("Unsupported algorithm id: {0}")]
+    UnsupportedAlgorithm(u8),
+    #[error("Malformed envelope: {0}")]
+    MalformedEnvelope(String),
+    #[error("Envelope was encrypted under a different key")]
+    EnvelopeKeyMismatch,
+    #[error("HPKE operation failed: {0}")]
+    HpkeError(String),
+    #[error("Invalid key derivation parameters: {0}")]
+    InvalidKdfParameters(String),
+    #[error("Invalid password hash: {0}")]
+    InvalidPasswordHash(String),
+    #[error("Password verification failed")]
+    PasswordVerificationFailed,
+    #[error("Malformed wrapped key: {0}")]
+    MalformedWrappedKey(String),
+    #[error("Key was wrapped under a different key-encryption key")]
+    WrongKeyEncryptionKey,
+    #[error("Wrapped key holds a {found:?} but a {expected:?} was requested")]
+    WrappedKeyKindMismatch { expected: WrappedKeyKind, found: WrappedKeyKind },
+    #[error("Key unwrap failed: integrity check failed (wrong key-encryption key or tampered data)")]
+    KeyUnwrapFailed,
+    #[error("Signature verification failed for batch items {0:?}")]
+    BatchVerificationFailed(Vec<usize>),
+    #[error("Unknown signing context: {0}")]
+    UnknownSigningContext(String),
+    #[error("Invalid multi-signature policy: {0}")]
+    InvalidMultisigPolicy(String),
+    #[error("Signer is not authorized by the multi-signature policy")]
+    SignerNotInPolicy,
+    #[error("Multi-signature threshold not met: {valid} of {required} required signatures")]
+    MultisigThresholdNotMet { required: usize, valid: usize },
+    #[error("Malformed minisign file: {0}")]
+    MalformedMinisignFile(String),
+    #[error("Minisign secret key is password protected; re-save it without a password (minisign -C -W)")]
+    EncryptedMinisignKey,
+    #[error("Signature was made by minisign key {found}, expected {expected}")]
+    MinisignKeyIdMismatch { expected: String, found: String },
+    #[error("Malformed key token: {0}")]
+    MalformedToken(String),
+    #[error("Forged key token: {0}")]
+    ForgedToken(String),
+    #[error("Key token expired at {expires_at}")]
+    TokenExpired { expires_at: u64 },
+    #[error("Invalid token attenuation: {0}")]
+    InvalidTokenAttenuation(String),
+    #[error("Public key is weak (small-order) and can't be trusted")]
+    WeakPublicKey,
+    #[error("Invalid derivation path: {0}")]
+    InvalidDerivationPath(String),
+    #[error("Malformed content ciphertext: {0}")]
+    MalformedContentCiphertext(String),
+    #[error("Content key epoch {epoch} is outside the accepted window {oldest}..={current}")]
+    ContentEpochRejected { epoch: u32, oldest: u32, current: u32 },
+    #[error("Message starts with the reserved signing-context domain; use sign_with_context")]
+    ReservedSigningDomain,
+    #[error("Input is too large for a legacy minisign signature (limit {0} bytes)")]
+    MinisignLegacyInputTooLarge(u64),
 }
 
 // Implement From trait to allow '?' conversion from signature::Error
@@ -63,50 +265,117 @@ impl From<SignatureTraitError> for CryptoError {
     }
 }
 
+// Allows '?' on I/O errors in the streaming and file-based APIs
+impl From<std::io::Error> for CryptoError {
+    fn from(e: std::io::Error) -> Self {
+        CryptoError::IoError(e.to_string())
+    }
+}
+
 // Function to generate a cryptographically secure random nonce
 pub fn generate_nonce() -> Nonce {
     ChaCha20Poly1305::generate_nonce(&mut OsRng).into()
 }
 
-// Function to encrypt data using ChaCha20-Poly1305
+// Function to encrypt data using ChaCha20-Poly1305.
+// If `associated_data` is provided it is authenticated (but not encrypted) alongside the
+// plaintext, and the exact same bytes must be supplied again to decrypt.
+// `None` is equivalent to empty associated data.
 pub fn encrypt_symmetric(
     key: &SymKey,
     plaintext: &[u8],
     nonce: &Nonce,
-    _associated_data: Option<&[u8]>,
+    associated_data: Option<&[u8]>,
 ) -> Result<Vec<u8>, CryptoError> {
-    let key = Key::from_slice(key);
+    let key = Key::from_slice(key.expose_secret());
     let cipher = ChaCha20Poly1305::new(key);
     let nonce = CryptoNonce::from_slice(nonce);
+    let payload = Payload {
+        msg: plaintext,
+        aad: associated_data.unwrap_or_default(),
+    };
 
     cipher
-        .encrypt(nonce, plaintext)
+        .encrypt(nonce, payload)
         .map_err(|e| CryptoError::EncryptionError(e.to_string()))
-
-    // Note: The current version of the `aead` crate's encrypt/decrypt methods
-    // using the `Aead` trait directly might not easily support associated data.
-    // We might need to use lower-level APIs or adapt if associated data is required frequently.
-    // For simplicity, this example omits associated data during encryption/decryption calls,
-    // assuming it's handled at a higher level or added if necessary using specific crate features.
-    // If associated_data is essential, the implementation would need adjustment, potentially using
-    // `AeadInPlace::encrypt_in_place_detached` or similar depending on the crate version.
 }
 
-// Function to decrypt data using ChaCha20-Poly1305
+// Function to decrypt data using ChaCha20-Poly1305.
+// Fails with `DecryptionError` if the key, nonce, ciphertext or associated data don't match
+// what was used during encryption.
 pub fn decrypt_symmetric(
     key: &SymKey,
     ciphertext: &[u8],
     nonce: &Nonce,
-    _associated_data: Option<&[u8]>,
+    associated_data: Option<&[u8]>,
 ) -> Result<Vec<u8>, CryptoError> {
-    let key = Key::from_slice(key);
+    let key = Key::from_slice(key.expose_secret());
     let cipher = ChaCha20Poly1305::new(key);
     let nonce = CryptoNonce::from_slice(nonce);
+    let payload = Payload {
+        msg: ciphertext,
+        aad: associated_data.unwrap_or_default(),
+    };
 
     cipher
-        .decrypt(nonce, ciphertext)
+        .decrypt(nonce, payload)
         .map_err(|_| CryptoError::DecryptionError) // Map generic AEAD error to our specific type
-        // See note in encrypt_symmetric regarding associated_data handling.
+}
+
+// --- Extended-Nonce Symmetric Encryption (XChaCha20-Poly1305) ---
+
+// XChaCha20-Poly1305 uses the same 32-byte key as ChaCha20-Poly1305 (see `SymKey`) but a
+// 192-bit nonce. Random nonces of this size can be generated for an effectively unbounded
+// number of messages under one key without a realistic risk of collision, which makes this
+// the preferred mode for long-lived keys such as those from `derive_symmetric_content_key`.
+pub const XNONCE_BYTES: usize = 24;
+
+pub type XNonce = [u8; XNONCE_BYTES];
+
+// Function to generate a cryptographically secure random 192-bit nonce
+pub fn generate_xnonce() -> XNonce {
+    XChaCha20Poly1305::generate_nonce(&mut OsRng).into()
+}
+
+// Function to encrypt data using XChaCha20-Poly1305.
+// Associated data is handled exactly as in `encrypt_symmetric`.
+pub fn encrypt_symmetric_xchacha(
+    key: &SymKey,
+    plaintext: &[u8],
+    nonce: &XNonce,
+    associated_data: Option<&[u8]>,
+) -> Result<Vec<u8>, CryptoError> {
+    let key = Key::from_slice(key.expose_secret());
+    let cipher = XChaCha20Poly1305::new(key);
+    let nonce = CryptoXNonce::from_slice(nonce);
+    let payload = Payload {
+        msg: plaintext,
+        aad: associated_data.unwrap_or_default(),
+    };
+
+    cipher
+        .encrypt(nonce, payload)
+        .map_err(|e| CryptoError::EncryptionError(e.to_string()))
+}
+
+// Function to decrypt data using XChaCha20-Poly1305
+pub fn decrypt_symmetric_xchacha(
+    key: &SymKey,
+    ciphertext: &[u8],
+    nonce: &XNonce,
+    associated_data: Option<&[u8]>,
+) -> Result<Vec<u8>, CryptoError> {
+    let key = Key::from_slice(key.expose_secret());
+    let cipher = XChaCha20Poly1305::new(key);
+    let nonce = CryptoXNonce::from_slice(nonce);
+    let payload = Payload {
+        msg: ciphertext,
+        aad: associated_data.unwrap_or_default(),
+    };
+
+    cipher
+        .decrypt(nonce, payload)
+        .map_err(|_| CryptoError::DecryptionError)
 }
 
 // --- Ed25519 Imports ---
@@ -135,24 +404,46 @@ impl SigningPublicKey {
             .map(|_| Self(*bytes)) // If ok, construct Self
             .map_err(|e| CryptoError::InternalError(format!("Failed to parse public key bytes: {}", e)))
     }
+
+    // Like `try_from_bytes`, but also rejects weak (small-order) points. A signature "by" a weak
+    // key can be forged without any secret, so keys that get stored or trusted should be
+    // parsed with this.
+    pub fn try_from_bytes_strict(bytes: &[u8; SIGNING_PUBLIC_KEY_BYTES]) -> Result<Self, CryptoError> {
+        let public_key = Self::try_from_bytes(bytes)?;
+        if public_key.is_weak() {
+            return Err(CryptoError::WeakPublicKey);
+        }
+        Ok(public_key)
+    }
+
+    // True for small-order points, which no honestly generated key can be
+    pub fn is_weak(&self) -> bool {
+        VerifyingKey::from_bytes(&self.0).map(|key| key.is_weak()).unwrap_or(true)
+    }
 }
 
-#[derive(Debug)] // Avoid Clone, Eq, PartialEq for secret key wrapper
-pub struct SigningSecretKey([u8; SIGNING_SECRET_KEY_BYTES]);
+secret_bytes! {
+    // Ed25519 secret key seed. Any 32 bytes are valid, so no validation is needed beyond length.
+    pub struct SigningSecretKey([u8; SIGNING_SECRET_KEY_BYTES]);
+}
 
-impl SigningSecretKey {
-    pub fn as_bytes(&self) -> &[u8; SIGNING_SECRET_KEY_BYTES] {
-        &self.0
+#[derive(Debug, Clone)]
+pub struct Signature([u8; SIGNATURE_BYTES]);
+
+// Signatures are authenticators, so they are compared in constant time like secrets
+impl ConstantTimeEq for Signature {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
     }
+}
 
-    // Simple constructor from bytes (no validation needed beyond length)
-    pub fn from_bytes(bytes: [u8; SIGNING_SECRET_KEY_BYTES]) -> Self {
-        Self(bytes)
+impl PartialEq for Signature {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Signature([u8; SIGNATURE_BYTES]);
+impl Eq for Signature {}
 
 impl Signature {
     pub fn as_bytes(&self) -> &[u8; SIGNATURE_BYTES] {
@@ -205,8 +496,18 @@ pub fn generate_signing_keypair() -> (SigningSecretKey, SigningPublicKey) {
     (signing_key.into(), verifying_key.into())
 }
 
-// Function to sign a message with a secret key
+// Function to sign a message with a secret key.
+// Messages starting with the signing-context domain are refused with
+// CryptoError::ReservedSigningDomain, so a raw signature can never pass verify_with_context.
 pub fn sign(secret_key: &SigningSecretKey, message: &[u8]) -> Result<Signature, CryptoError> {
+    if message.starts_with(signing_context::SIGNING_CONTEXT_DOMAIN) {
+        return Err(CryptoError::ReservedSigningDomain);
+    }
+    sign_unchecked(secret_key, message)
+}
+
+// `sign` without the reserved-domain check, for sign_with_context
+pub(crate) fn sign_unchecked(secret_key: &SigningSecretKey, message: &[u8]) -> Result<Signature, CryptoError> {
     // Reconstruct the SigningKey from the secret bytes.
     // Note: This assumes the SigningSecretKey wrapper only holds the secret scalar bytes.
     // Note 2: SigningKey::from_bytes will panic if the length is incorrect. We assume
@@ -218,18 +519,118 @@ pub fn sign(secret_key: &SigningSecretKey, message: &[u8]) -> Result<Signature,
     Ok(signature.into())
 }
 
-// Function to verify a signature
+// How strictly Ed25519 signatures are checked.
+//
+// Permissive is the plain RFC 8032 check (`Verifier::verify`), which is what `verify` has always
+// done. Strict uses `verify_strict`: it additionally rejects weak (small-order) public keys and
+// signatures whose R is small-order or not canonically encoded. Under Strict, a signature is
+// valid for at most one (key, message) pair, which is what ledgers, tokens and other
+// consensus-style uses need. The signing-context, prehash, multisig, token and minisign layers
+// always verify strictly. There is deliberately no default: callers of `verify_with_mode` and
+// `verify_batch` pick a mode explicitly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationMode {
+    Permissive,
+    Strict,
+}
+
+// Function to verify a signature (permissive, see VerificationMode)
 pub fn verify(
     public_key: &SigningPublicKey,
     message: &[u8],
     signature: &Signature,
+) -> Result<(), CryptoError> {
+    verify_with_mode(public_key, message, signature, VerificationMode::Permissive)
+}
+
+/// Verifies a signature with an explicitly chosen `VerificationMode`.
+///
+/// # Returns
+/// * `Ok(())` if the signature is valid under `mode`.
+/// * `Err(CryptoError::WeakPublicKey)` in strict mode if the public key is small-order.
+/// * `Err(CryptoError::SignatureVerificationFailed)` if the signature is invalid (in strict mode
+///   this includes non-canonical or small-order R).
+pub fn verify_with_mode(
+    public_key: &SigningPublicKey,
+    message: &[u8],
+    signature: &Signature,
+    mode: VerificationMode,
 ) -> Result<(), CryptoError> {
     let verifying_key = VerifyingKey::try_from(public_key)?;
     let ed_signature = <EdSignature as TryFrom<&[u8]>>::try_from(&signature.0)?;
 
-    verifying_key
-        .verify(message, &ed_signature)
-        .map_err(|_| CryptoError::SignatureVerificationFailed)
+    match mode {
+        VerificationMode::Permissive => verifying_key
+            .verify(message, &ed_signature)
+            .map_err(|_| CryptoError::SignatureVerificationFailed),
+        VerificationMode::Strict => {
+            if verifying_key.is_weak() {
+                return Err(CryptoError::WeakPublicKey);
+            }
+            verifying_key
+                .verify_strict(message, &ed_signature)
+                .map_err(|_| CryptoError::SignatureVerificationFailed)
+        }
+    }
+}
+
+// Function to verify many (public key, message, signature) items at once.
+// In Permissive mode all items are first checked together with ed25519-dalek's batch
+// verification, which is much faster than checking them one by one. If the batch fails, every
+// item is re-checked with `verify_with_mode` to find out which ones are bad. The batch equation
+// is cofactored while `verify` is not, so a batch can pass with an item `verify` would reject
+// (only possible with a small-order component, never for honestly generated keys).
+// Strict mode skips the batch and checks every item with `verify_with_mode`, since a passing
+// batch says nothing about the strict per-item rules.
+//
+// Returns `Err(CryptoError::BatchVerificationFailed(indices))` listing the failing items in order.
+pub fn verify_batch(
+    items: &[(&SigningPublicKey, &[u8], &Signature)],
+    mode: VerificationMode,
+) -> Result<(), CryptoError> {
+    if items.is_empty() {
+        return Ok(());
+    }
+    if mode == VerificationMode::Strict {
+        return verify_each(items, mode);
+    }
+
+    // Anything that doesn't even parse goes straight to the per-item pass
+    let parsed: Result<Vec<(VerifyingKey, EdSignature)>, CryptoError> = items
+        .iter()
+        .map(|(public_key, _, signature)| {
+            let verifying_key = VerifyingKey::try_from(*public_key)?;
+            let ed_signature = <EdSignature as TryFrom<&[u8]>>::try_from(&signature.0)?;
+            Ok((verifying_key, ed_signature))
+        })
+        .collect();
+
+    if let Ok(parsed) = parsed {
+        let messages: Vec<&[u8]> = items.iter().map(|(_, message, _)| *message).collect();
+        let (verifying_keys, signatures): (Vec<VerifyingKey>, Vec<EdSignature>) = parsed.into_iter().unzip();
+        if ed25519_dalek::verify_batch(&messages, &signatures, &verifying_keys).is_ok() {
+            return Ok(());
+        }
+    }
+
+    verify_each(items, mode)
+}
+
+// Per-item pass of verify_batch
+fn verify_each(items: &[(&SigningPublicKey, &[u8], &Signature)], mode: VerificationMode) -> Result<(), CryptoError> {
+    let failed: Vec<usize> = items
+        .iter()
+        .enumerate()
+        .filter(|(_, (public_key, message, signature))| {
+            verify_with_mode(public_key, message, signature, mode).is_err()
+        })
+        .map(|(index, _)| index)
+        .collect();
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(CryptoError::BatchVerificationFailed(failed))
+    }
 }
 
 // --- X25519 Imports ---
@@ -249,22 +650,37 @@ pub const SHARED_SECRET_BYTES: usize = 32;
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct KeyExchangePublicKey([u8; KEY_EXCHANGE_PUBLIC_KEY_BYTES]);
 
-#[derive(Debug)] // Avoid Clone, Eq, PartialEq for secret key wrapper
-pub struct KeyExchangeSecretKey([u8; KEY_EXCHANGE_SECRET_KEY_BYTES]);
+secret_bytes! {
+    // X25519 static secret (clamping happens when the key is used)
+    pub struct KeyExchangeSecretKey([u8; KEY_EXCHANGE_SECRET_KEY_BYTES]);
+}
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct SharedSecret([u8; SHARED_SECRET_BYTES]);
+secret_bytes! {
+    pub struct SharedSecret([u8; SHARED_SECRET_BYTES]);
+}
 
-// Implement Deref to easily access inner bytes
-impl std::ops::Deref for KeyExchangePublicKey {
-    type Target = [u8; KEY_EXCHANGE_PUBLIC_KEY_BYTES];
-    fn deref(&self) -> &Self::Target {
+impl KeyExchangePublicKey {
+    pub fn as_bytes(&self) -> &[u8; KEY_EXCHANGE_PUBLIC_KEY_BYTES] {
         &self.0
     }
+
+    // Simple constructor from bytes. Any 32 bytes are a valid X25519 public key encoding;
+    // low-order points are caught by the all-zero check in key_exchange.
+    pub fn from_bytes(bytes: [u8; KEY_EXCHANGE_PUBLIC_KEY_BYTES]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl KeyExchangeSecretKey {
+    // Computes the public key corresponding to this secret key
+    pub fn public_key(&self) -> KeyExchangePublicKey {
+        X25519PublicKey::from(&X25519StaticSecret::from(self)).into()
+    }
 }
 
-impl std::ops::Deref for SharedSecret {
-    type Target = [u8; SHARED_SECRET_BYTES];
+// Implement Deref to easily access inner bytes
+impl std::ops::Deref for KeyExchangePublicKey {
+    type Target = [u8; KEY_EXCHANGE_PUBLIC_KEY_BYTES];
     fn deref(&self) -> &Self::Target {
         &self.0
     }
@@ -309,8 +725,8 @@ impl From<X25519SharedSecret> for SharedSecret {
 
 // Function to generate a new X25519 static key pair
 pub fn generate_key_exchange_keypair() -> (KeyExchangeSecretKey, KeyExchangePublicKey) {
-    let mut csprng = RandOsRng;
-    let static_secret = X25519StaticSecret::random_from_rng(&mut csprng);
+    let csprng = RandOsRng;
+    let static_secret = X25519StaticSecret::random_from_rng(csprng);
     let public_key = X25519PublicKey::from(&static_secret);
     (static_secret.into(), public_key.into())
 }
@@ -328,7 +744,8 @@ pub fn key_exchange(
 
     // Check for all-zero shared secret (potential indication of weak/invalid public key)
     // While not strictly mandated by RFC7748, it's a common safety check.
-    if shared_secret.as_bytes() == &[0u8; 32] {
+    // was_contributory performs the all-zero check in constant time.
+    if !shared_secret.was_contributory() {
         return Err(CryptoError::KeyExchangeError(
             "Potential weak key detected: resulted in all-zero shared secret".to_string(),
         ));
@@ -337,6 +754,42 @@ pub fn key_exchange(
     Ok(shared_secret.into())
 }
 
+// --- Ed25519 -> X25519 Conversion ---
+//
+// Lets one published Ed25519 identity key also receive encrypted data, using the birational map
+// from the Edwards curve to its Montgomery form (the same conversion as libsodium's
+// crypto_sign_ed25519_pk_to_curve25519 / crypto_sign_ed25519_sk_to_curve25519). Using one key
+// for both signing and key exchange is safe for Ed25519 + X25519 (see "On using the same key pair
+// for Ed25519 and an X25519 based KEM", https://eprint.iacr.org/2021/509), but a separate,
+// rotatable key exchange key is still preferable where one can be published.
+
+/// Converts an Ed25519 public key to the X25519 public key of the same identity.
+///
+/// # Returns
+/// * `Ok(KeyExchangePublicKey)` that matches `signing_secret_key_to_key_exchange` of the secret.
+/// * `Err(CryptoError::WeakPublicKey)` if the key is small-order (its X25519 form would make every
+///   shared secret predictable).
+pub fn signing_public_key_to_key_exchange(
+    public_key: &SigningPublicKey,
+) -> Result<KeyExchangePublicKey, CryptoError> {
+    let verifying_key = VerifyingKey::try_from(public_key)?;
+    if verifying_key.is_weak() {
+        return Err(CryptoError::WeakPublicKey);
+    }
+    Ok(KeyExchangePublicKey(verifying_key.to_montgomery().to_bytes()))
+}
+
+// Converts an Ed25519 secret key to the X25519 secret key of the same identity: the clamped
+// first half of SHA-512(seed), which is the scalar Ed25519 signs with.
+pub fn signing_secret_key_to_key_exchange(secret_key: &SigningSecretKey) -> KeyExchangeSecretKey {
+    let scalar = Zeroizing::new(SigningKey::from_bytes(&secret_key.0).to_scalar_bytes());
+    let mut key_exchange_secret = KeyExchangeSecretKey::from_bytes(*scalar);
+    key_exchange_secret.0[0] &= 248;
+    key_exchange_secret.0[31] &= 127;
+    key_exchange_secret.0[31] |= 64;
+    key_exchange_secret
+}
+
 // --- HKDF Imports ---
 use hkdf::Hkdf;
 use sha2::Sha256;
@@ -344,13 +797,49 @@ use sha2::Sha256;
 // --- Key Derivation ---
 
 // Define key types as specified in the protocol
-// Using fixed-size arrays for secrets where appropriate.
-// MasterSeed is often variable length (e.g., from BIP39), represented as Vec<u8>.
-pub type MasterSeed = Vec<u8>;
+// Using fixed-size secret newtypes where appropriate.
+// MasterSeed is often variable length (e.g., 64 bytes from BIP39), so it wraps a Vec<u8>.
+#[derive(Zeroize, ZeroizeOnDrop)]
+pub struct MasterSeed(Vec<u8>);
+
+impl MasterSeed {
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+
+    pub fn expose_secret(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl std::fmt::Debug for MasterSeed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("MasterSeed([REDACTED])")
+    }
+}
+
+impl ConstantTimeEq for MasterSeed {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.as_slice().ct_eq(other.0.as_slice())
+    }
+}
+
+impl PartialEq for MasterSeed {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for MasterSeed {}
+
 pub const ROOT_IDENTITY_SECRET_BYTES: usize = 32;
-pub type RootIdentitySecret = [u8; ROOT_IDENTITY_SECRET_BYTES];
+secret_bytes! {
+    pub struct RootIdentitySecret([u8; ROOT_IDENTITY_SECRET_BYTES]);
+}
 pub const CONTENT_MASTER_KEY_BYTES: usize = 32;
-pub type ContentMasterKey = [u8; CONTENT_MASTER_KEY_BYTES];
+secret_bytes! {
+    pub struct ContentMasterKey([u8; CONTENT_MASTER_KEY_BYTES]);
+}
 // Re-use existing types:
 // SymmetricContentKey = SymKey ([u8; 32])
 // TokenSigningKeyPair = (SigningSecretKey, SigningPublicKey)
@@ -373,8 +862,8 @@ fn derive_hkdf_output(
 pub fn derive_root_identity_secret(master_seed: &MasterSeed) -> Result<RootIdentitySecret, CryptoError> {
     let salt = b"master";
     let info = b"root-identity";
-    let mut rik = [0u8; ROOT_IDENTITY_SECRET_BYTES];
-    derive_hkdf_output(master_seed, salt, info, &mut rik)?;
+    let mut rik = RootIdentitySecret::from_bytes([0u8; ROOT_IDENTITY_SECRET_BYTES]);
+    derive_hkdf_output(master_seed.expose_secret(), salt, info, &mut rik.0)?;
     Ok(rik)
 }
 
@@ -386,8 +875,8 @@ pub fn derive_identity_signing_keypair(
     let salt = b"identity-signing";
     let info = purpose_string.as_bytes();
     // Ed25519 secret key seed is 32 bytes
-    let mut key_seed = [0u8; SIGNING_SECRET_KEY_BYTES];
-    derive_hkdf_output(rik, salt, info, &mut key_seed)?;
+    let mut key_seed = Zeroizing::new([0u8; SIGNING_SECRET_KEY_BYTES]);
+    derive_hkdf_output(rik.expose_secret(), salt, info, key_seed.as_mut())?;
 
     // Generate the full keypair from the derived seed
     let signing_key = SigningKey::from_bytes(&key_seed); // This handles the Ed25519 specific part
@@ -396,6 +885,21 @@ pub fn derive_identity_signing_keypair(
     Ok((signing_key.into(), verifying_key.into()))
 }
 
+// 2b. RIK -> Key Exchange Key Pair (X25519)
+// Lets encryption keys be restored from the mnemonic like signing keys. The 32 derived bytes are
+// the static secret as-is; clamping happens when the key is used, as for generated keys.
+pub fn derive_key_exchange_keypair(
+    rik: &RootIdentitySecret,
+    purpose_string: &str, // e.g., "primary-key-exchange" or a per-account label
+) -> Result<(KeyExchangeSecretKey, KeyExchangePublicKey), CryptoError> {
+    let salt = b"key-exchange";
+    let info = purpose_string.as_bytes();
+    let mut secret_key = KeyExchangeSecretKey::from_bytes([0u8; KEY_EXCHANGE_SECRET_KEY_BYTES]);
+    derive_hkdf_output(rik.expose_secret(), salt, info, &mut secret_key.0)?;
+    let public_key = secret_key.public_key();
+    Ok((secret_key, public_key))
+}
+
 // 3. RIK -> Content Master Key (CMK)
 pub fn derive_content_master_key(
     rik: &RootIdentitySecret,
@@ -403,8 +907,29 @@ pub fn derive_content_master_key(
 ) -> Result<ContentMasterKey, CryptoError> {
     let salt = b"content-key-derivation";
     let info = content_id; // Use the content_id directly as info
-    let mut cmk = [0u8; CONTENT_MASTER_KEY_BYTES];
-    derive_hkdf_output(rik, salt, info, &mut cmk)?;
+    let mut cmk = ContentMasterKey::from_bytes([0u8; CONTENT_MASTER_KEY_BYTES]);
+    derive_hkdf_output(rik.expose_secret(), salt, info, &mut cmk.0)?;
+    Ok(cmk)
+}
+
+// 3b. RIK -> Content Master Key for a key epoch
+// Epoch 0 is the original derivation above, so content encrypted before epochs existed keeps
+// its keys. Later epochs use their own salt and put the fixed-width epoch ahead of the content
+// id, so no (epoch, content id) pair can produce another pair's info string.
+pub fn derive_content_master_key_for_epoch(
+    rik: &RootIdentitySecret,
+    content_id: &[u8],
+    epoch: u32,
+) -> Result<ContentMasterKey, CryptoError> {
+    if epoch == 0 {
+        return derive_content_master_key(rik, content_id);
+    }
+    let salt = b"content-key-epoch";
+    let mut info = Vec::with_capacity(4 + content_id.len());
+    info.extend_from_slice(&epoch.to_be_bytes());
+    info.extend_from_slice(content_id);
+    let mut cmk = ContentMasterKey::from_bytes([0u8; CONTENT_MASTER_KEY_BYTES]);
+    derive_hkdf_output(rik.expose_secret(), salt, &info, &mut cmk.0)?;
     Ok(cmk)
 }
 
@@ -412,8 +937,8 @@ pub fn derive_content_master_key(
 pub fn derive_symmetric_content_key(cmk: &ContentMasterKey) -> Result<SymKey, CryptoError> {
     let salt = b"symmetric-encryption";
     let info = b"slice-encryption";
-    let mut sck = [0u8; SYMMETRIC_KEY_BYTES]; // SYMMETRIC_KEY_BYTES = 32
-    derive_hkdf_output(cmk, salt, info, &mut sck)?;
+    let mut sck = SymKey::from_bytes([0u8; SYMMETRIC_KEY_BYTES]); // SYMMETRIC_KEY_BYTES = 32
+    derive_hkdf_output(cmk.expose_secret(), salt, info, &mut sck.0)?;
     Ok(sck)
 }
 
@@ -423,8 +948,8 @@ pub fn derive_token_signing_keypair(
 ) -> Result<(SigningSecretKey, SigningPublicKey), CryptoError> {
     let salt = b"token-signing";
     let info = b"transactable-key-token";
-    let mut key_seed = [0u8; SIGNING_SECRET_KEY_BYTES];
-    derive_hkdf_output(cmk, salt, info, &mut key_seed)?;
+    let mut key_seed = Zeroizing::new([0u8; SIGNING_SECRET_KEY_BYTES]);
+    derive_hkdf_output(cmk.expose_secret(), salt, info, key_seed.as_mut())?;
 
     // Generate the full keypair from the derived seed
     let signing_key = SigningKey::from_bytes(&key_seed);
@@ -454,20 +979,34 @@ pub fn validate_mnemonic(mnemonic_phrase: &str) -> Result<(), CryptoError> {
 /// Uses the English wordlist and an empty passphrase by default.
 ///
 /// # Returns
-/// * `Ok<[u8; 64]>` containing the derived 64-byte seed.
+/// * `Ok(MasterSeed)` containing the derived 64-byte seed.
 /// * `Err(CryptoError::MnemonicToSeedError)` if conversion fails (e.g., invalid mnemonic).
-pub fn mnemonic_to_seed(mnemonic_phrase: &str) -> Result<[u8; 64], CryptoError> {
+pub fn mnemonic_to_seed(mnemonic_phrase: &str) -> Result<MasterSeed, CryptoError> {
+    mnemonic_to_seed_with_passphrase(mnemonic_phrase, "") // Use empty passphrase as standard
+}
+
+/// Converts a valid BIP-39 mnemonic phrase plus a passphrase (the "25th word") into its 64-byte seed.
+/// The passphrase is NFKD-normalized as BIP-39 requires. Any passphrase gives a valid but different
+/// seed, so a wrong passphrase can't be detected here; an empty passphrase matches `mnemonic_to_seed`.
+///
+/// # Returns
+/// * `Ok(MasterSeed)` containing the derived 64-byte seed.
+/// * `Err(CryptoError::MnemonicToSeedError)` if conversion fails (e.g., invalid mnemonic).
+pub fn mnemonic_to_seed_with_passphrase(
+    mnemonic_phrase: &str,
+    passphrase: &str,
+) -> Result<MasterSeed, CryptoError> {
     let mnemonic = Mnemonic::parse_in(Language::English, mnemonic_phrase)
         .map_err(|e| CryptoError::MnemonicToSeedError(format!("Invalid mnemonic: {}", e)))?;
-    // Use the Mnemonic::to_seed method which returns [u8; 64]
-    Ok(mnemonic.to_seed("")) // Use empty passphrase as standard
+    // Mnemonic::to_seed returns a plain [u8; 64]; wipe it once copied into the MasterSeed
+    let seed = Zeroizing::new(mnemonic.to_seed(passphrase));
+    Ok(MasterSeed::from_bytes(seed.to_vec()))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use rand::RngCore; // This is needed for generate_random_key in tests
-    use hex; // Ensure use hex is present inside the test module
 
     // --- Hashing Tests (Updated for SHA3-256) ---
     #[test]
@@ -512,11 +1051,12 @@ mod tests {
 
     // --- Symmetric Encryption Tests ---
 
-    fn generate_random_key() -> SymKey {
+    // Shared with the module tests
+    pub(crate) fn generate_random_key() -> SymKey {
         let mut key = [0u8; SYMMETRIC_KEY_BYTES];
         // Use OsRng from chacha20poly1305::aead imports
         OsRng.fill_bytes(&mut key); 
-        key
+        SymKey::from_bytes(key)
     }
 
     #[test]
@@ -610,9 +1150,130 @@ mod tests {
         assert_eq!(result.unwrap_err(), CryptoError::DecryptionError);
     }
 
-    // --- Add tests for Associated Data (AAD) if the implementation supports it ---
-    // #[test]
-    // fn test_encrypt_decrypt_roundtrip_with_aad() { ... }
+    // --- Associated Data (AAD) Tests ---
+
+    #[test]
+    fn test_encrypt_decrypt_roundtrip_with_aad() {
+        let key = generate_random_key();
+        let nonce = generate_nonce();
+        let plaintext = b"bound to a content id";
+        let associated_data = Some(&b"content-id-1234"[..]);
+
+        let ciphertext = encrypt_symmetric(&key, plaintext, &nonce, associated_data)
+            .expect("Encryption failed");
+        let decrypted_plaintext = decrypt_symmetric(&key, &ciphertext, &nonce, associated_data)
+            .expect("Decryption failed");
+
+        assert_eq!(decrypted_plaintext, plaintext);
+    }
+
+    #[test]
+    fn test_decrypt_wrong_aad() {
+        let key = generate_random_key();
+        let nonce = generate_nonce();
+        let plaintext = b"bound to a content id";
+
+        let ciphertext = encrypt_symmetric(&key, plaintext, &nonce, Some(b"content-id-1"))
+            .expect("Encryption failed");
+
+        let result = decrypt_symmetric(&key, &ciphertext, &nonce, Some(b"content-id-2"));
+        assert_eq!(result.unwrap_err(), CryptoError::DecryptionError);
+    }
+
+    #[test]
+    fn test_decrypt_missing_aad() {
+        let key = generate_random_key();
+        let nonce = generate_nonce();
+        let plaintext = b"bound to a content id";
+
+        let ciphertext = encrypt_symmetric(&key, plaintext, &nonce, Some(b"content-id-1"))
+            .expect("Encryption failed");
+
+        // Omitting the AAD on decryption must fail just like a mismatched AAD
+        let result = decrypt_symmetric(&key, &ciphertext, &nonce, None);
+        assert_eq!(result.unwrap_err(), CryptoError::DecryptionError);
+    }
+
+    #[test]
+    fn test_empty_aad_equivalent_to_none() {
+        let key = generate_random_key();
+        let nonce = generate_nonce();
+        let plaintext = b"no header";
+
+        let ciphertext = encrypt_symmetric(&key, plaintext, &nonce, None)
+            .expect("Encryption failed");
+        let decrypted_plaintext = decrypt_symmetric(&key, &ciphertext, &nonce, Some(&[]))
+            .expect("Decryption failed");
+
+        assert_eq!(decrypted_plaintext, plaintext);
+    }
+
+    // --- XChaCha20-Poly1305 Tests ---
+
+    #[test]
+    fn test_generate_xnonce_length() {
+        let nonce = generate_xnonce();
+        assert_eq!(nonce.len(), XNONCE_BYTES);
+    }
+
+    #[test]
+    fn test_xchacha_encrypt_decrypt_roundtrip() {
+        let key = generate_random_key();
+        let nonce = generate_xnonce();
+        let plaintext = b"this is a secret message";
+
+        let ciphertext = encrypt_symmetric_xchacha(&key, plaintext, &nonce, Some(b"header"))
+            .expect("Encryption failed");
+        assert_ne!(ciphertext.as_slice(), plaintext);
+
+        let decrypted_plaintext = decrypt_symmetric_xchacha(&key, &ciphertext, &nonce, Some(b"header"))
+            .expect("Decryption failed");
+        assert_eq!(decrypted_plaintext, plaintext);
+    }
+
+    #[test]
+    fn test_xchacha_decrypt_failures() {
+        let key = generate_random_key();
+        let nonce = generate_xnonce();
+        let plaintext = b"don't tamper with me";
+
+        let ciphertext = encrypt_symmetric_xchacha(&key, plaintext, &nonce, Some(b"header"))
+            .expect("Encryption failed");
+
+        // Wrong key
+        let result = decrypt_symmetric_xchacha(&generate_random_key(), &ciphertext, &nonce, Some(b"header"));
+        assert_eq!(result.unwrap_err(), CryptoError::DecryptionError);
+
+        // Wrong nonce
+        let result = decrypt_symmetric_xchacha(&key, &ciphertext, &generate_xnonce(), Some(b"header"));
+        assert_eq!(result.unwrap_err(), CryptoError::DecryptionError);
+
+        // Wrong associated data
+        let result = decrypt_symmetric_xchacha(&key, &ciphertext, &nonce, Some(b"other"));
+        assert_eq!(result.unwrap_err(), CryptoError::DecryptionError);
+
+        // Tampered ciphertext
+        let mut tampered = ciphertext.clone();
+        tampered[0] ^= 0x01;
+        let result = decrypt_symmetric_xchacha(&key, &tampered, &nonce, Some(b"header"));
+        assert_eq!(result.unwrap_err(), CryptoError::DecryptionError);
+    }
+
+    #[test]
+    fn test_xchacha_not_interchangeable_with_chacha() {
+        // The same key and the first 12 bytes of the extended nonce must not decrypt
+        // under the 96-bit construction.
+        let key = generate_random_key();
+        let xnonce = generate_xnonce();
+        let plaintext = b"mode confusion";
+
+        let ciphertext = encrypt_symmetric_xchacha(&key, plaintext, &xnonce, None)
+            .expect("Encryption failed");
+        let short_nonce: Nonce = xnonce[..NONCE_BYTES].try_into().unwrap();
+
+        let result = decrypt_symmetric(&key, &ciphertext, &short_nonce, None);
+        assert_eq!(result.unwrap_err(), CryptoError::DecryptionError);
+    }
 
     // --- Ed25519 Signing Tests ---
 
@@ -698,6 +1359,125 @@ mod tests {
         }
     }
 
+    // --- Batch Verification Tests ---
+
+    fn signed_items(count: usize) -> Vec<(SigningPublicKey, Vec<u8>, Signature)> {
+        (0..count)
+            .map(|i| {
+                let (secret_key, public_key) = generate_signing_keypair();
+                let message = format!("ledger entry {}", i).into_bytes();
+                let signature = sign(&secret_key, &message).unwrap();
+                (public_key, message, signature)
+            })
+            .collect()
+    }
+
+    fn as_batch(items: &[(SigningPublicKey, Vec<u8>, Signature)]) -> Vec<(&SigningPublicKey, &[u8], &Signature)> {
+        items.iter().map(|(pk, msg, sig)| (pk, msg.as_slice(), sig)).collect()
+    }
+
+    #[test]
+    fn test_verify_batch_all_valid() {
+        let items = signed_items(16);
+        assert!(verify_batch(&as_batch(&items), VerificationMode::Permissive).is_ok());
+        assert!(verify_batch(&as_batch(&items), VerificationMode::Strict).is_ok());
+        assert!(verify_batch(&[], VerificationMode::Permissive).is_ok());
+    }
+
+    #[test]
+    fn test_verify_batch_reports_failing_indices() {
+        let mut items = signed_items(8);
+        // Tampered message, signature from another item, and a flipped signature bit
+        items[1].1 = b"tampered".to_vec();
+        items[4].2 = items[5].2.clone();
+        items[6].2.0[0] ^= 0x01;
+
+        assert_eq!(
+            verify_batch(&as_batch(&items), VerificationMode::Permissive).unwrap_err(),
+            CryptoError::BatchVerificationFailed(vec![1, 4, 6])
+        );
+    }
+
+    #[test]
+    fn test_verify_batch_matches_single_verify() {
+        let mut items = signed_items(4);
+        // An unparseable signature (s >= group order) fails in both paths
+        items[2].2.0[63] = 0xFF;
+        let single: Vec<usize> = items
+            .iter()
+            .enumerate()
+            .filter(|(_, (pk, msg, sig))| verify(pk, msg, sig).is_err())
+            .map(|(i, _)| i)
+            .collect();
+        assert_eq!(single, vec![2]);
+        assert_eq!(
+            verify_batch(&as_batch(&items), VerificationMode::Permissive).unwrap_err(),
+            CryptoError::BatchVerificationFailed(single)
+        );
+    }
+
+    // --- Strict Verification Tests ---
+
+    // The identity point is a small-order (weak) key. With R = identity and S = 0 the RFC 8032
+    // equation holds for every message, so permissive verification accepts a "signature" that
+    // needed no secret at all.
+    fn weak_key_forgery() -> (SigningPublicKey, Signature) {
+        let mut identity = [0u8; SIGNING_PUBLIC_KEY_BYTES];
+        identity[0] = 1;
+        let mut signature = [0u8; SIGNATURE_BYTES];
+        signature[0] = 1;
+        (
+            SigningPublicKey::try_from_bytes(&identity).unwrap(),
+            Signature::try_from_bytes(&signature).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_verify_modes_accept_valid_signatures() {
+        let (secret_key, public_key) = generate_signing_keypair();
+        let signature = sign(&secret_key, b"ledger entry").unwrap();
+        assert!(!public_key.is_weak());
+        assert!(verify_with_mode(&public_key, b"ledger entry", &signature, VerificationMode::Strict).is_ok());
+        assert!(verify_with_mode(&public_key, b"ledger entry", &signature, VerificationMode::Permissive).is_ok());
+        assert_eq!(
+            verify_with_mode(&public_key, b"other entry", &signature, VerificationMode::Strict).unwrap_err(),
+            CryptoError::SignatureVerificationFailed
+        );
+    }
+
+    #[test]
+    fn test_strict_mode_rejects_weak_keys() {
+        let (weak_key, forged) = weak_key_forgery();
+        assert!(weak_key.is_weak());
+
+        // `verify` keeps its permissive behavior
+        assert!(verify(&weak_key, b"anything", &forged).is_ok());
+        assert_eq!(
+            verify_with_mode(&weak_key, b"anything", &forged, VerificationMode::Strict).unwrap_err(),
+            CryptoError::WeakPublicKey
+        );
+        assert_eq!(
+            SigningPublicKey::try_from_bytes_strict(weak_key.as_bytes()).unwrap_err(),
+            CryptoError::WeakPublicKey
+        );
+        assert_eq!(
+            verify_with_context(&weak_key, SigningContext::LedgerEntry, b"anything", &forged).unwrap_err(),
+            CryptoError::WeakPublicKey
+        );
+
+        // The cofactored batch equation accepts the forgery; only strict mode catches it
+        let mut items = signed_items(3);
+        items[1] = (weak_key, b"anything".to_vec(), forged);
+        assert!(verify_batch(&as_batch(&items), VerificationMode::Permissive).is_ok());
+        assert_eq!(
+            verify_batch(&as_batch(&items), VerificationMode::Strict).unwrap_err(),
+            CryptoError::BatchVerificationFailed(vec![1])
+        );
+
+        let (_, public_key) = generate_signing_keypair();
+        assert!(SigningPublicKey::try_from_bytes_strict(public_key.as_bytes()).is_ok());
+    }
+
     // --- X25519 Key Exchange Tests ---
 
     #[test]
@@ -756,12 +1536,60 @@ mod tests {
         }
     }
 
+    // --- Ed25519 -> X25519 Conversion Tests ---
+
+    // libsodium test/default/ed25519_convert.c
+    #[test]
+    fn test_ed25519_to_x25519_libsodium_vector() {
+        let seed: [u8; SIGNING_SECRET_KEY_BYTES] =
+            hex::decode("421151a459faeade3d247115f94aedae42318124095afabe4d1451a559faedee")
+                .unwrap()
+                .try_into()
+                .unwrap();
+        let secret_key = SigningSecretKey::from_bytes(seed);
+        let public_key: SigningPublicKey = SigningKey::from_bytes(&seed).verifying_key().into();
+
+        let curve_public = signing_public_key_to_key_exchange(&public_key).unwrap();
+        let curve_secret = signing_secret_key_to_key_exchange(&secret_key);
+        assert_eq!(
+            hex::encode(curve_public.as_bytes()),
+            "f1814f0e8ff1043d8a44d25babff3cedcae6c22c3edaa48f857ae70de2baae50"
+        );
+        assert_eq!(
+            hex::encode(curve_secret.expose_secret()),
+            "8052030376d47112be7f73ed7a019293dd12ad910b654455798b4667d73de166"
+        );
+    }
+
+    #[test]
+    fn test_converted_keys_agree() {
+        let (signing_secret, signing_public) = generate_signing_keypair();
+        let recipient_secret = signing_secret_key_to_key_exchange(&signing_secret);
+        let recipient_public = signing_public_key_to_key_exchange(&signing_public).unwrap();
+        assert_eq!(recipient_secret.public_key(), recipient_public);
+
+        // Anyone with the published signing key can now agree on a secret with its owner
+        let (sender_secret, sender_public) = generate_key_exchange_keypair();
+        let sender_shared = key_exchange(&sender_secret, &recipient_public).unwrap();
+        let recipient_shared = key_exchange(&recipient_secret, &sender_public).unwrap();
+        assert_eq!(sender_shared, recipient_shared);
+    }
+
+    #[test]
+    fn test_weak_signing_key_not_converted() {
+        let (weak_key, _) = weak_key_forgery();
+        assert_eq!(
+            signing_public_key_to_key_exchange(&weak_key).unwrap_err(),
+            CryptoError::WeakPublicKey
+        );
+    }
+
     // --- HKDF Key Derivation Tests ---
 
     // Basic test for RIK derivation determinism
     #[test]
     fn test_derive_rik_deterministic() {
-        let seed: MasterSeed = vec![0x01, 0x02, 0x03, 0x04];
+        let seed = MasterSeed::from_bytes(vec![0x01, 0x02, 0x03, 0x04]);
         let rik1 = derive_root_identity_secret(&seed).expect("Derivation 1 failed");
         let rik2 = derive_root_identity_secret(&seed).expect("Derivation 2 failed");
         assert_eq!(rik1, rik2);
@@ -770,8 +1598,8 @@ mod tests {
     // Test that different master seeds yield different RIKs
     #[test]
     fn test_derive_rik_distinct_seeds() {
-        let seed1: MasterSeed = vec![0x01, 0x02, 0x03, 0x04];
-        let seed2: MasterSeed = vec![0x05, 0x06, 0x07, 0x08];
+        let seed1 = MasterSeed::from_bytes(vec![0x01, 0x02, 0x03, 0x04]);
+        let seed2 = MasterSeed::from_bytes(vec![0x05, 0x06, 0x07, 0x08]);
         let rik1 = derive_root_identity_secret(&seed1).expect("Derivation 1 failed");
         let rik2 = derive_root_identity_secret(&seed2).expect("Derivation 2 failed");
         assert_ne!(rik1, rik2);
@@ -780,7 +1608,7 @@ mod tests {
     // Test Identity Signing Key derivation (determinism and distinctness)
     #[test]
     fn test_derive_identity_signing_keypair() {
-        let rik: RootIdentitySecret = [1u8; 32];
+        let rik = RootIdentitySecret::from_bytes([1u8; 32]);
         let (sk1a, pk1a) = derive_identity_signing_keypair(&rik, "purpose1").unwrap();
         let (sk1b, pk1b) = derive_identity_signing_keypair(&rik, "purpose1").unwrap();
         let (sk2, pk2) = derive_identity_signing_keypair(&rik, "purpose2").unwrap();
@@ -791,10 +1619,33 @@ mod tests {
         assert_ne!(pk1a.0, pk2.0); // Different info should yield different public keys
     }
 
+    // Test Key Exchange Key derivation (determinism, distinctness and usability)
+    #[test]
+    fn test_derive_key_exchange_keypair() {
+        let rik = RootIdentitySecret::from_bytes([1u8; 32]);
+        let (sk1a, pk1a) = derive_key_exchange_keypair(&rik, "account-1").unwrap();
+        let (sk1b, pk1b) = derive_key_exchange_keypair(&rik, "account-1").unwrap();
+        let (sk2, pk2) = derive_key_exchange_keypair(&rik, "account-2").unwrap();
+
+        assert_eq!(sk1a, sk1b);
+        assert_eq!(pk1a, pk1b);
+        assert_ne!(sk1a, sk2);
+        assert_ne!(pk1a, pk2);
+        assert_eq!(sk1a.public_key(), pk1a);
+
+        // The same purpose on the signing branch gives unrelated key material
+        let (signing_sk, _) = derive_identity_signing_keypair(&rik, "account-1").unwrap();
+        assert_ne!(&signing_sk.0, sk1a.expose_secret());
+
+        let shared_1 = key_exchange(&sk1a, &pk2).unwrap();
+        let shared_2 = key_exchange(&sk2, &pk1a).unwrap();
+        assert_eq!(shared_1, shared_2);
+    }
+
     // Test Content Master Key derivation (determinism and distinctness)
     #[test]
     fn test_derive_content_master_key() {
-        let rik: RootIdentitySecret = [2u8; 32];
+        let rik = RootIdentitySecret::from_bytes([2u8; 32]);
         let content_id1 = b"content_1";
         let content_id2 = b"different_content_2";
         let cmk1a = derive_content_master_key(&rik, content_id1).unwrap();
@@ -808,17 +1659,17 @@ mod tests {
     // Test Symmetric Content Key derivation (determinism)
     #[test]
     fn test_derive_symmetric_content_key() {
-        let cmk: ContentMasterKey = [3u8; 32];
+        let cmk = ContentMasterKey::from_bytes([3u8; 32]);
         let sck1 = derive_symmetric_content_key(&cmk).unwrap();
         let sck2 = derive_symmetric_content_key(&cmk).unwrap();
         assert_eq!(sck1, sck2);
-        assert_eq!(sck1.len(), SYMMETRIC_KEY_BYTES);
+        assert_eq!(sck1.expose_secret().len(), SYMMETRIC_KEY_BYTES);
     }
 
     // Test Token Signing Key derivation (determinism)
     #[test]
     fn test_derive_token_signing_keypair() {
-        let cmk: ContentMasterKey = [4u8; 32];
+        let cmk = ContentMasterKey::from_bytes([4u8; 32]);
         let (sk1, pk1) = derive_token_signing_keypair(&cmk).unwrap();
         let (sk2, pk2) = derive_token_signing_keypair(&cmk).unwrap();
         assert_eq!(sk1.0, sk2.0);
@@ -829,8 +1680,8 @@ mod tests {
     // Test that different CMKs yield different SCKs and Token Signing Keys
     #[test]
     fn test_derive_keys_distinct_cmk() {
-        let cmk1: ContentMasterKey = [5u8; 32];
-        let cmk2: ContentMasterKey = [6u8; 32];
+        let cmk1 = ContentMasterKey::from_bytes([5u8; 32]);
+        let cmk2 = ContentMasterKey::from_bytes([6u8; 32]);
 
         let sck1 = derive_symmetric_content_key(&cmk1).unwrap();
         let sck2 = derive_symmetric_content_key(&cmk2).unwrap();
@@ -841,6 +1692,73 @@ mod tests {
         assert_ne!(token_sk1.0, token_sk2.0);
     }
 
+    // --- Secret Newtype Tests ---
+
+    #[test]
+    fn test_secret_debug_is_redacted() {
+        let key = SymKey::from_bytes([0xAB; SYMMETRIC_KEY_BYTES]);
+        let (signing_secret, _) = generate_signing_keypair();
+        let seed = MasterSeed::from_bytes(vec![0xAB; 64]);
+
+        assert_eq!(format!("{:?}", key), "SymKey([REDACTED])");
+        assert_eq!(format!("{:?}", signing_secret), "SigningSecretKey([REDACTED])");
+        assert_eq!(format!("{:?}", seed), "MasterSeed([REDACTED])");
+        // Neither the hex nor the decimal form of the bytes may leak
+        for debug in [format!("{:?}", key), format!("{:#?}", seed)] {
+            assert!(!debug.contains("ab") && !debug.contains("171"));
+        }
+    }
+
+    #[test]
+    fn test_secret_zeroize() {
+        let mut cmk = ContentMasterKey::from_bytes([7u8; CONTENT_MASTER_KEY_BYTES]);
+        cmk.zeroize();
+        assert_eq!(cmk.expose_secret(), &[0u8; CONTENT_MASTER_KEY_BYTES]);
+
+        let mut seed = MasterSeed::from_bytes(vec![7u8; 64]);
+        seed.zeroize();
+        assert!(seed.expose_secret().iter().all(|&b| b == 0));
+    }
+
+    // --- Constant-Time Equality Tests ---
+
+    #[test]
+    fn test_secret_ct_eq() {
+        let a = SymKey::from_bytes([1u8; SYMMETRIC_KEY_BYTES]);
+        let b = SymKey::from_bytes([1u8; SYMMETRIC_KEY_BYTES]);
+        let mut last_differs = [1u8; SYMMETRIC_KEY_BYTES];
+        last_differs[SYMMETRIC_KEY_BYTES - 1] = 2;
+        let c = SymKey::from_bytes(last_differs);
+
+        assert!(bool::from(a.ct_eq(&b)));
+        assert!(!bool::from(a.ct_eq(&c)));
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+
+        assert_eq!(MasterSeed::from_bytes(vec![1, 2, 3]), MasterSeed::from_bytes(vec![1, 2, 3]));
+        assert_ne!(MasterSeed::from_bytes(vec![1, 2, 3]), MasterSeed::from_bytes(vec![1, 2]));
+    }
+
+    #[test]
+    fn test_signature_ct_eq() {
+        let (secret_key, _) = generate_signing_keypair();
+        let signature1 = sign(&secret_key, b"message").unwrap();
+        let signature2 = sign(&secret_key, b"message").unwrap(); // Ed25519 is deterministic
+        let other = sign(&secret_key, b"other message").unwrap();
+
+        assert!(bool::from(signature1.ct_eq(&signature2)));
+        assert_eq!(signature1, signature2);
+        assert_ne!(signature1, other);
+    }
+
+    #[test]
+    fn test_constant_time_eq_helper() {
+        assert!(constant_time_eq(b"tag-bytes", b"tag-bytes"));
+        assert!(!constant_time_eq(b"tag-bytes", b"tag-bytez"));
+        assert!(!constant_time_eq(b"tag-bytes", b"tag-byte"));
+        assert!(constant_time_eq(b"", b""));
+    }
+
     // --- Mnemonic Tests (adjust if needed) ---
     #[test]
     fn test_validate_mnemonic_valid() {
@@ -897,11 +1815,24 @@ mod tests {
         let result = mnemonic_to_seed(mnemonic);
         assert!(result.is_ok());
         let seed = result.unwrap();
-        assert_eq!(seed.len(), 64); // BIP-39 seeds are 512 bits (64 bytes)
+        assert_eq!(seed.expose_secret().len(), 64); // BIP-39 seeds are 512 bits (64 bytes)
 
         // Example expected seed hex for the above mnemonic (verify with external tool if needed)
         let expected_seed_hex = "c8461859f479021518f663c6157e1478a7a61c46940f4317f6f20491c12b97431e13b89d783a6118401197e510c56859730e61bc584e569f55a651c16089f737";
-        assert_eq!(hex::encode(seed), expected_seed_hex);
+        assert_eq!(hex::encode(seed.expose_secret()), expected_seed_hex);
+    }
+
+    #[test]
+    fn test_mnemonic_to_seed_with_passphrase() {
+        // BIP-39 reference vector (passphrase "TREZOR")
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let seed = mnemonic_to_seed_with_passphrase(mnemonic, "TREZOR").unwrap();
+        let expected_seed_hex = "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04";
+        assert_eq!(hex::encode(seed.expose_secret()), expected_seed_hex);
+
+        // An empty passphrase is the plain mnemonic seed; any other passphrase is a different wallet
+        assert_eq!(mnemonic_to_seed_with_passphrase(mnemonic, "").unwrap(), mnemonic_to_seed(mnemonic).unwrap());
+        assert_ne!(mnemonic_to_seed_with_passphrase(mnemonic, "TREZOR ").unwrap(), seed);
     }
 
     #[test]
@@ -0,0 +1,520 @@
+// --- Minisign-Compatible Key and Signature Files ---
+//
+// Reads and writes the file formats of minisign (https://jedisct1.github.io/minisign/), so files
+// we sign can be checked with `minisign -V` and keys can move between the two.
+//
+// Public key file (`.pub`):
+//   untrusted comment: <text>
+//   base64("Ed" || key id (8) || public key (32))
+//
+// Secret key file (`.key`), unencrypted form only:
+//   untrusted comment: <text>
+//   base64("Ed" || kdf "\0\0" || checksum alg "B2" || kdf salt (32) || opslimit (8) ||
+//          memlimit (8) || key id (8) || secret seed (32) || public key (32) || checksum (32))
+//   checksum = BLAKE2b-256("Ed" || key id || secret seed || public key)
+// minisign encrypts secret keys with scrypt by default; those have to be re-saved without a
+// password (`minisign -C -W`) before they can be read here.
+//
+// Detached signature (`.minisig`):
+//   untrusted comment: <text>
+//   base64(alg (2) || key id (8) || signature (64))
+//   trusted comment: <text>
+//   base64(global signature (64))
+// alg "ED" signs BLAKE2b-512(file) and is what we produce; the legacy alg "Ed" signs the file
+// itself and is only accepted when verifying, for inputs up to MINISIGN_MAX_LEGACY_INPUT_BYTES
+// (the whole input has to be held in memory). The global signature covers
+// signature || trusted comment, so the trusted comment can't be altered.
+//
+// Like libsodium (which minisign uses), verification is strict: weak public keys and
+// non-canonical signatures are rejected.
+
+use crate::{
+    hash_data, sign, verify_with_mode, ConstantTimeEq, CryptoError, Signature, SigningPublicKey,
+    SigningSecretKey, VerificationMode, SIGNATURE_BYTES, SIGNING_PUBLIC_KEY_BYTES, SIGNING_SECRET_KEY_BYTES,
+};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use blake2::digest::consts::U32;
+use blake2::{Blake2b, Blake2b512, Digest};
+use ed25519_dalek::SigningKey;
+use std::io::{self, Read};
+use zeroize::Zeroizing;
+
+pub const MINISIGN_KEY_ID_BYTES: usize = 8;
+// Largest input a legacy "Ed" signature is checked against (64 MiB)
+pub const MINISIGN_MAX_LEGACY_INPUT_BYTES: u64 = 64 * 1024 * 1024;
+
+const SIG_ALG_LEGACY: [u8; 2] = *b"Ed";
+const SIG_ALG_PREHASHED: [u8; 2] = *b"ED";
+const KDF_ALG_NONE: [u8; 2] = [0, 0];
+const KDF_ALG_SCRYPT: [u8; 2] = *b"Sc";
+const CHECKSUM_ALG: [u8; 2] = *b"B2";
+
+const UNTRUSTED_PREFIX: &str = "untrusted comment: ";
+const TRUSTED_PREFIX: &str = "trusted comment: ";
+
+const PUBLIC_KEY_FILE_BYTES: usize = 2 + MINISIGN_KEY_ID_BYTES + SIGNING_PUBLIC_KEY_BYTES;
+const SIGNATURE_LINE_BYTES: usize = 2 + MINISIGN_KEY_ID_BYTES + SIGNATURE_BYTES;
+const SECRET_KEY_FILE_BYTES: usize = 2 + 2 + 2 + 32 + 8 + 8 + MINISIGN_KEY_ID_BYTES + 64 + 32;
+
+// Bytes read per chunk while hashing a file (64 KiB)
+const MINISIGN_READ_CHUNK_BYTES: usize = 64 * 1024;
+
+// Identifies which key made a signature. Stored as raw bytes; minisign displays it as the
+// uppercase hex of the bytes read as a little-endian u64.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MinisignKeyId(pub [u8; MINISIGN_KEY_ID_BYTES]);
+
+impl MinisignKeyId {
+    // Our keys get an id derived from the public key, so re-exporting a key keeps its id
+    pub fn for_public_key(public_key: &SigningPublicKey) -> Self {
+        let mut id = [0u8; MINISIGN_KEY_ID_BYTES];
+        id.copy_from_slice(&hash_data(public_key.as_bytes())[..MINISIGN_KEY_ID_BYTES]);
+        Self(id)
+    }
+
+    // The form minisign prints, e.g. in "minisign public key E7620F1842B4E81F"
+    pub fn to_hex(&self) -> String {
+        format!("{:016X}", u64::from_le_bytes(self.0))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinisignPublicKey {
+    pub key_id: MinisignKeyId,
+    pub public_key: SigningPublicKey,
+}
+
+impl MinisignPublicKey {
+    pub fn new(public_key: SigningPublicKey) -> Self {
+        Self { key_id: MinisignKeyId::for_public_key(&public_key), public_key }
+    }
+
+    // The single base64 line, as passed to `minisign -P`
+    pub fn to_base64(&self) -> String {
+        let mut bytes = Vec::with_capacity(PUBLIC_KEY_FILE_BYTES);
+        bytes.extend_from_slice(&SIG_ALG_LEGACY);
+        bytes.extend_from_slice(&self.key_id.0);
+        bytes.extend_from_slice(self.public_key.as_bytes());
+        BASE64.encode(bytes)
+    }
+
+    /// Parses the base64 line of a public key file (or a key given with `minisign -P`).
+    ///
+    /// # Returns
+    /// * `Err(CryptoError::MalformedMinisignFile)` if it isn't a minisign Ed25519 public key.
+    /// * `Err(CryptoError::WeakPublicKey)` if the key is small-order.
+    pub fn from_base64(line: &str) -> Result<Self, CryptoError> {
+        let bytes = decode_line(line, PUBLIC_KEY_FILE_BYTES, "public key")?;
+        if bytes[..2] != SIG_ALG_LEGACY {
+            return Err(malformed("public key is not an Ed25519 key"));
+        }
+        let key_id = MinisignKeyId(bytes[2..10].try_into().expect("slice length is fixed"));
+        let public_key =
+            SigningPublicKey::try_from_bytes_strict(&bytes[10..].try_into().expect("slice length is fixed"))?;
+        Ok(Self { key_id, public_key })
+    }
+
+    pub fn to_file_string(&self) -> String {
+        format!(
+            "{}minisign public key {}\n{}\n",
+            UNTRUSTED_PREFIX,
+            self.key_id.to_hex(),
+            self.to_base64()
+        )
+    }
+
+    pub fn from_file_str(contents: &str) -> Result<Self, CryptoError> {
+        let mut lines = contents.lines();
+        expect_untrusted_comment(lines.next())?;
+        Self::from_base64(lines.next().ok_or_else(|| malformed("missing public key line"))?)
+    }
+}
+
+#[derive(Debug)]
+pub struct MinisignSecretKey {
+    pub key_id: MinisignKeyId,
+    pub secret_key: SigningSecretKey,
+}
+
+impl MinisignSecretKey {
+    pub fn new(secret_key: SigningSecretKey) -> Self {
+        let public_key = public_key_for(&secret_key);
+        Self { key_id: MinisignKeyId::for_public_key(&public_key), secret_key }
+    }
+
+    pub fn public_key(&self) -> MinisignPublicKey {
+        MinisignPublicKey { key_id: self.key_id, public_key: public_key_for(&self.secret_key) }
+    }
+
+    // Writes the unencrypted form (what `minisign -G -W` produces)
+    pub fn to_file_string(&self) -> Zeroizing<String> {
+        let public_key = public_key_for(&self.secret_key);
+        let mut bytes = Zeroizing::new(Vec::with_capacity(SECRET_KEY_FILE_BYTES));
+        bytes.extend_from_slice(&SIG_ALG_LEGACY);
+        bytes.extend_from_slice(&KDF_ALG_NONE);
+        bytes.extend_from_slice(&CHECKSUM_ALG);
+        // kdf salt, opslimit and memlimit are unused without a kdf
+        bytes.extend_from_slice(&[0u8; 32 + 8 + 8]);
+        bytes.extend_from_slice(&self.key_id.0);
+        bytes.extend_from_slice(self.secret_key.expose_secret());
+        bytes.extend_from_slice(public_key.as_bytes());
+        bytes.extend_from_slice(&secret_key_checksum(&self.key_id, self.secret_key.expose_secret(), public_key.as_bytes()));
+
+        Zeroizing::new(format!(
+            "{}minisign secret key {}\n{}\n",
+            UNTRUSTED_PREFIX,
+            self.key_id.to_hex(),
+            *Zeroizing::new(BASE64.encode(&*bytes))
+        ))
+    }
+
+    /// Parses an unencrypted minisign secret key file.
+    ///
+    /// # Returns
+    /// * `Err(CryptoError::EncryptedMinisignKey)` if the key is password protected.
+    /// * `Err(CryptoError::MalformedMinisignFile)` if the file is malformed, its checksum doesn't
+    ///   match, or the stored public key doesn't belong to the secret key.
+    pub fn from_file_str(contents: &str) -> Result<Self, CryptoError> {
+        let mut lines = contents.lines();
+        expect_untrusted_comment(lines.next())?;
+        let line = lines.next().ok_or_else(|| malformed("missing secret key line"))?;
+        let bytes = Zeroizing::new(decode_line(line, SECRET_KEY_FILE_BYTES, "secret key")?);
+
+        if bytes[..2] != SIG_ALG_LEGACY || bytes[4..6] != CHECKSUM_ALG {
+            return Err(malformed("unsupported secret key algorithm"));
+        }
+        if bytes[2..4] == KDF_ALG_SCRYPT {
+            return Err(CryptoError::EncryptedMinisignKey);
+        }
+        if bytes[2..4] != KDF_ALG_NONE {
+            return Err(malformed("unsupported secret key encryption"));
+        }
+
+        let key = &bytes[54..];
+        let key_id = MinisignKeyId(key[..8].try_into().expect("slice length is fixed"));
+        let seed = &key[8..8 + SIGNING_SECRET_KEY_BYTES];
+        let stored_public = &key[40..72];
+        let checksum = secret_key_checksum(&key_id, seed, stored_public);
+        if !bool::from(checksum.ct_eq(&key[72..])) {
+            return Err(malformed("secret key checksum mismatch"));
+        }
+
+        let mut seed_array = SigningSecretKey::from_bytes([0u8; SIGNING_SECRET_KEY_BYTES]);
+        seed_array.0.copy_from_slice(seed);
+        if public_key_for(&seed_array).as_bytes()[..] != *stored_public {
+            return Err(malformed("public key does not match secret key"));
+        }
+        Ok(Self { key_id, secret_key: seed_array })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinisignSignature {
+    pub key_id: MinisignKeyId,
+    // true for alg "ED" (signature over BLAKE2b-512 of the file), false for legacy "Ed"
+    pub prehashed: bool,
+    pub signature: Signature,
+    pub untrusted_comment: String,
+    pub trusted_comment: String,
+    pub global_signature: Signature,
+}
+
+impl MinisignSignature {
+    pub fn to_file_string(&self) -> String {
+        let mut line = Vec::with_capacity(SIGNATURE_LINE_BYTES);
+        line.extend_from_slice(if self.prehashed { &SIG_ALG_PREHASHED } else { &SIG_ALG_LEGACY });
+        line.extend_from_slice(&self.key_id.0);
+        line.extend_from_slice(self.signature.as_bytes());
+        format!(
+            "{}{}\n{}\n{}{}\n{}\n",
+            UNTRUSTED_PREFIX,
+            self.untrusted_comment,
+            BASE64.encode(line),
+            TRUSTED_PREFIX,
+            self.trusted_comment,
+            BASE64.encode(self.global_signature.as_bytes())
+        )
+    }
+
+    pub fn from_file_str(contents: &str) -> Result<Self, CryptoError> {
+        let mut lines = contents.lines();
+        let untrusted_comment = expect_untrusted_comment(lines.next())?;
+        let line = decode_line(
+            lines.next().ok_or_else(|| malformed("missing signature line"))?,
+            SIGNATURE_LINE_BYTES,
+            "signature",
+        )?;
+        let prehashed = match [line[0], line[1]] {
+            SIG_ALG_PREHASHED => true,
+            SIG_ALG_LEGACY => false,
+            _ => return Err(malformed("unsupported signature algorithm")),
+        };
+        let trusted_comment = lines
+            .next()
+            .and_then(|l| l.trim_end_matches('\r').strip_prefix(TRUSTED_PREFIX))
+            .ok_or_else(|| malformed("missing trusted comment"))?;
+        let global = decode_line(
+            lines.next().ok_or_else(|| malformed("missing global signature"))?,
+            SIGNATURE_BYTES,
+            "global signature",
+        )?;
+
+        Ok(Self {
+            key_id: MinisignKeyId(line[2..10].try_into().expect("slice length is fixed")),
+            prehashed,
+            signature: Signature::try_from_bytes(&line[10..].try_into().expect("slice length is fixed"))?,
+            untrusted_comment: untrusted_comment.to_string(),
+            trusted_comment: trusted_comment.to_string(),
+            global_signature: Signature::try_from_bytes(&global[..].try_into().expect("slice length is fixed"))?,
+        })
+    }
+}
+
+/// Signs everything readable from `reader` the way `minisign -S` does (prehashed, alg "ED").
+///
+/// `trusted_comment` is signed along with the file; it must be a single line.
+///
+/// # Returns
+/// * `Err(CryptoError::MalformedMinisignFile)` if `trusted_comment` contains a line break.
+/// * `Err(CryptoError::IoError)` if reading fails.
+pub fn minisign_sign<R: Read>(
+    secret_key: &MinisignSecretKey,
+    reader: &mut R,
+    trusted_comment: &str,
+) -> Result<MinisignSignature, CryptoError> {
+    if trusted_comment.contains(['\r', '\n']) {
+        return Err(malformed("trusted comment must be a single line"));
+    }
+    let digest = blake2b_512_reader(reader)?;
+    let signature = sign(&secret_key.secret_key, &digest)?;
+    let global_signature = sign(&secret_key.secret_key, &global_message(&signature, trusted_comment))?;
+
+    Ok(MinisignSignature {
+        key_id: secret_key.key_id,
+        prehashed: true,
+        signature,
+        untrusted_comment: "signature from minisign secret key".to_string(),
+        trusted_comment: trusted_comment.to_string(),
+        global_signature,
+    })
+}
+
+/// Verifies a minisign signature over everything readable from `reader`, including the trusted
+/// comment. Legacy (non-prehashed) signatures read the whole input into memory, so their input is
+/// capped at `MINISIGN_MAX_LEGACY_INPUT_BYTES`.
+///
+/// # Returns
+/// * `Ok(())` if both the file signature and the trusted comment signature are valid.
+/// * `Err(CryptoError::MinisignKeyIdMismatch)` if the signature was made by a different key.
+/// * `Err(CryptoError::SignatureVerificationFailed)` if either signature is invalid.
+/// * `Err(CryptoError::MinisignLegacyInputTooLarge)` if a legacy signature's input is over the cap.
+/// * `Err(CryptoError::IoError)` if reading fails.
+pub fn minisign_verify<R: Read>(
+    public_key: &MinisignPublicKey,
+    reader: &mut R,
+    signature: &MinisignSignature,
+) -> Result<(), CryptoError> {
+    if signature.key_id != public_key.key_id {
+        return Err(CryptoError::MinisignKeyIdMismatch {
+            expected: public_key.key_id.to_hex(),
+            found: signature.key_id.to_hex(),
+        });
+    }
+    if signature.prehashed {
+        let digest = blake2b_512_reader(reader)?;
+        verify_with_mode(&public_key.public_key, &digest, &signature.signature, VerificationMode::Strict)?;
+    } else {
+        let mut message = Vec::new();
+        reader.take(MINISIGN_MAX_LEGACY_INPUT_BYTES + 1).read_to_end(&mut message)?;
+        if message.len() as u64 > MINISIGN_MAX_LEGACY_INPUT_BYTES {
+            return Err(CryptoError::MinisignLegacyInputTooLarge(MINISIGN_MAX_LEGACY_INPUT_BYTES));
+        }
+        verify_with_mode(&public_key.public_key, &message, &signature.signature, VerificationMode::Strict)?;
+    }
+    verify_with_mode(
+        &public_key.public_key,
+        &global_message(&signature.signature, &signature.trusted_comment),
+        &signature.global_signature,
+        VerificationMode::Strict,
+    )
+}
+
+fn public_key_for(secret_key: &SigningSecretKey) -> SigningPublicKey {
+    let verifying_key = SigningKey::from_bytes(secret_key.expose_secret()).verifying_key();
+    SigningPublicKey::try_from_bytes(verifying_key.as_bytes()).expect("derived public keys are valid")
+}
+
+fn secret_key_checksum(key_id: &MinisignKeyId, seed: &[u8], public_key: &[u8]) -> [u8; 32] {
+    let mut hasher = Blake2b::<U32>::new();
+    hasher.update(SIG_ALG_LEGACY);
+    hasher.update(key_id.0);
+    hasher.update(seed);
+    hasher.update(public_key);
+    hasher.finalize().into()
+}
+
+fn global_message(signature: &Signature, trusted_comment: &str) -> Vec<u8> {
+    let mut message = Vec::with_capacity(SIGNATURE_BYTES + trusted_comment.len());
+    message.extend_from_slice(signature.as_bytes());
+    message.extend_from_slice(trusted_comment.as_bytes());
+    message
+}
+
+fn blake2b_512_reader<R: Read>(reader: &mut R) -> Result<[u8; 64], CryptoError> {
+    let mut hasher = Blake2b512::new();
+    let mut buf = vec![0u8; MINISIGN_READ_CHUNK_BYTES];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => return Ok(hasher.finalize().into()),
+            Ok(n) => hasher.update(&buf[..n]),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+fn expect_untrusted_comment(line: Option<&str>) -> Result<&str, CryptoError> {
+    line.and_then(|l| l.trim_end_matches('\r').strip_prefix(UNTRUSTED_PREFIX))
+        .ok_or_else(|| malformed("missing untrusted comment"))
+}
+
+fn decode_line(line: &str, expected_len: usize, what: &str) -> Result<Vec<u8>, CryptoError> {
+    let bytes = BASE64
+        .decode(line.trim())
+        .map_err(|e| CryptoError::MalformedMinisignFile(format!("invalid {} base64: {}", what, e)))?;
+    if bytes.len() != expected_len {
+        return Err(CryptoError::MalformedMinisignFile(format!(
+            "{} is {} bytes, expected {}",
+            what,
+            bytes.len(),
+            expected_len
+        )));
+    }
+    Ok(bytes)
+}
+
+fn malformed(reason: &str) -> CryptoError {
+    CryptoError::MalformedMinisignFile(reason.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_signing_keypair;
+
+    // Key and signature made by the minisign tool over the 4-byte file "test" (legacy alg "Ed")
+    const MINISIGN_PUBLIC_KEY: &str = "RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3";
+    const MINISIGN_SIGNATURE: &str = "untrusted comment: signature from minisign secret key
+RWQf6LRCGA9i59SLOFxz6NxvASXDJeRtuZykwQepbDEGt87ig1BNpWaVWuNrm73YiIiJbq71Wi+dP9eKL8OC351vwIasSSbXxwA=
+trusted comment: timestamp:1555779966\tfile:test
+QtKMXWyYcwdpZAlPF7tE2ENJkRd1ujvKjlj1m9RtHTBnZPa5WKU5uWRs5GoP5M/VqE81QFuMKI5k/SfNQUaOAA==
+";
+
+    fn minisign_keypair() -> MinisignSecretKey {
+        MinisignSecretKey::new(generate_signing_keypair().0)
+    }
+
+    #[test]
+    fn test_verifies_minisign_tool_signature() {
+        let public_key = MinisignPublicKey::from_base64(MINISIGN_PUBLIC_KEY).unwrap();
+        assert_eq!(public_key.key_id.to_hex(), "E7620F1842B4E81F");
+
+        let signature = MinisignSignature::from_file_str(MINISIGN_SIGNATURE).unwrap();
+        assert!(!signature.prehashed);
+        assert_eq!(signature.trusted_comment, "timestamp:1555779966\tfile:test");
+        assert!(minisign_verify(&public_key, &mut &b"test"[..], &signature).is_ok());
+        assert_eq!(
+            minisign_verify(&public_key, &mut &b"tesT"[..], &signature).unwrap_err(),
+            CryptoError::SignatureVerificationFailed
+        );
+
+        // Round-trips byte for byte
+        assert_eq!(signature.to_file_string(), MINISIGN_SIGNATURE);
+    }
+
+    #[test]
+    fn test_legacy_input_is_capped() {
+        let public_key = MinisignPublicKey::from_base64(MINISIGN_PUBLIC_KEY).unwrap();
+        let signature = MinisignSignature::from_file_str(MINISIGN_SIGNATURE).unwrap();
+        let mut oversized = io::repeat(0).take(MINISIGN_MAX_LEGACY_INPUT_BYTES + 1);
+        assert_eq!(
+            minisign_verify(&public_key, &mut oversized, &signature).unwrap_err(),
+            CryptoError::MinisignLegacyInputTooLarge(MINISIGN_MAX_LEGACY_INPUT_BYTES)
+        );
+    }
+
+    #[test]
+    fn test_trusted_comment_is_authenticated() {
+        let public_key = MinisignPublicKey::from_base64(MINISIGN_PUBLIC_KEY).unwrap();
+        let tampered = MINISIGN_SIGNATURE.replace("file:test", "file:evil");
+        let signature = MinisignSignature::from_file_str(&tampered).unwrap();
+        assert_eq!(
+            minisign_verify(&public_key, &mut &b"test"[..], &signature).unwrap_err(),
+            CryptoError::SignatureVerificationFailed
+        );
+    }
+
+    #[test]
+    fn test_sign_verify_roundtrip() {
+        let secret_key = minisign_keypair();
+        let public_key = secret_key.public_key();
+        let data = vec![7u8; 3 * MINISIGN_READ_CHUNK_BYTES + 5];
+
+        let signature = minisign_sign(&secret_key, &mut data.as_slice(), "timestamp:0\tfile:data.bin\thashed").unwrap();
+        assert!(signature.prehashed);
+
+        let parsed = MinisignSignature::from_file_str(&signature.to_file_string()).unwrap();
+        assert_eq!(parsed, signature);
+        assert!(minisign_verify(&public_key, &mut data.as_slice(), &parsed).is_ok());
+        assert!(minisign_verify(&public_key, &mut &data[1..], &parsed).is_err());
+
+        let other = minisign_keypair().public_key();
+        assert!(matches!(
+            minisign_verify(&other, &mut data.as_slice(), &parsed),
+            Err(CryptoError::MinisignKeyIdMismatch { .. })
+        ));
+        assert!(minisign_sign(&secret_key, &mut &b""[..], "two\nlines").is_err());
+    }
+
+    #[test]
+    fn test_key_files_roundtrip() {
+        let secret_key = minisign_keypair();
+        let public_key = secret_key.public_key();
+
+        let public_file = public_key.to_file_string();
+        assert!(public_file.starts_with(&format!("untrusted comment: minisign public key {}\n", public_key.key_id.to_hex())));
+        assert_eq!(MinisignPublicKey::from_file_str(&public_file).unwrap(), public_key);
+
+        let secret_file = secret_key.to_file_string();
+        let parsed = MinisignSecretKey::from_file_str(&secret_file).unwrap();
+        assert_eq!(parsed.key_id, secret_key.key_id);
+        assert_eq!(parsed.secret_key, secret_key.secret_key);
+        assert_eq!(MinisignKeyId::for_public_key(&public_key.public_key), public_key.key_id);
+    }
+
+    #[test]
+    fn test_secret_key_file_checks() {
+        let secret_file = minisign_keypair().to_file_string();
+        let line = secret_file.lines().nth(1).unwrap();
+        let bytes = BASE64.decode(line).unwrap();
+        let rebuild = |bytes: &[u8]| format!("untrusted comment: x\n{}\n", BASE64.encode(bytes));
+
+        let mut corrupted = bytes.clone();
+        corrupted[70] ^= 1;
+        assert!(matches!(
+            MinisignSecretKey::from_file_str(&rebuild(&corrupted)),
+            Err(CryptoError::MalformedMinisignFile(_))
+        ));
+
+        let mut encrypted = bytes.clone();
+        encrypted[2..4].copy_from_slice(b"Sc");
+        assert_eq!(
+            MinisignSecretKey::from_file_str(&rebuild(&encrypted)).unwrap_err(),
+            CryptoError::EncryptedMinisignKey
+        );
+
+        assert!(MinisignSecretKey::from_file_str(&rebuild(&bytes[1..])).is_err());
+        assert!(MinisignSecretKey::from_file_str(line).is_err());
+    }
+}
@@ -0,0 +1,373 @@
+// --- m-of-n Multi-Signature Policies ---
+//
+// A policy is a set of authorized Ed25519 signing keys plus a threshold m. An action covered by
+// the policy is approved once at least m distinct authorized keys have each produced an ordinary
+// `sign` signature over the same message; nothing here changes how individual signatures work.
+//
+// Canonical encoding (what `policy_hash` commits to):
+//   "PNMS" || version (1) || threshold (1) || key count n (1) || keys (32 * n)
+// Keys are sorted by their bytes and unique, so the same set of keys and threshold always gives
+// the same bytes and hash no matter the order the keys were supplied in.
+//
+// Signatures are verified strictly (see `VerificationMode`) and weak keys can't be part of a
+// policy, since anyone can produce signatures that permissive verification accepts for them.
+
+use crate::{
+    hash_data, verify_with_mode, CryptoError, Hash, Signature, SigningPublicKey, VerificationMode,
+    SIGNING_PUBLIC_KEY_BYTES,
+};
+
+pub const MULTISIG_POLICY_MAGIC: [u8; 4] = *b"PNMS";
+pub const MULTISIG_POLICY_VERSION: u8 = 1;
+// The key count is encoded in one byte
+pub const MULTISIG_MAX_KEYS: usize = u8::MAX as usize;
+
+const MULTISIG_POLICY_HEADER_BYTES: usize = 4 + 1 + 1 + 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultisigPolicy {
+    threshold: u8,
+    keys: Vec<SigningPublicKey>,
+}
+
+impl MultisigPolicy {
+    /// Creates a policy requiring `threshold` of `keys` to sign.
+    ///
+    /// # Returns
+    /// * `Err(CryptoError::WeakPublicKey)` if any key is small-order.
+    /// * `Err(CryptoError::InvalidMultisigPolicy)` if a key appears twice, there are no keys or
+    ///   more than `MULTISIG_MAX_KEYS`, or the threshold is 0 or larger than the number of keys.
+    pub fn new(mut keys: Vec<SigningPublicKey>, threshold: usize) -> Result<Self, CryptoError> {
+        if keys.iter().any(|key| key.is_weak()) {
+            return Err(CryptoError::WeakPublicKey);
+        }
+        keys.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
+        if keys.windows(2).any(|pair| pair[0] == pair[1]) {
+            return Err(CryptoError::InvalidMultisigPolicy("duplicate key".to_string()));
+        }
+        if keys.is_empty() || keys.len() > MULTISIG_MAX_KEYS {
+            return Err(CryptoError::InvalidMultisigPolicy(format!(
+                "policy must have between 1 and {} keys, got {}",
+                MULTISIG_MAX_KEYS,
+                keys.len()
+            )));
+        }
+        if threshold == 0 || threshold > keys.len() {
+            return Err(CryptoError::InvalidMultisigPolicy(format!(
+                "threshold must be between 1 and {}, got {}",
+                keys.len(),
+                threshold
+            )));
+        }
+        Ok(Self { threshold: threshold as u8, keys })
+    }
+
+    pub fn threshold(&self) -> usize {
+        self.threshold as usize
+    }
+
+    // Authorized keys in canonical (sorted) order
+    pub fn keys(&self) -> &[SigningPublicKey] {
+        &self.keys
+    }
+
+    pub fn is_authorized(&self, public_key: &SigningPublicKey) -> bool {
+        self.position(public_key).is_some()
+    }
+
+    fn position(&self, public_key: &SigningPublicKey) -> Option<usize> {
+        self.keys
+            .binary_search_by(|key| key.as_bytes().cmp(public_key.as_bytes()))
+            .ok()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(MULTISIG_POLICY_HEADER_BYTES + self.keys.len() * SIGNING_PUBLIC_KEY_BYTES);
+        bytes.extend_from_slice(&MULTISIG_POLICY_MAGIC);
+        bytes.push(MULTISIG_POLICY_VERSION);
+        bytes.push(self.threshold);
+        bytes.push(self.keys.len() as u8);
+        for key in &self.keys {
+            bytes.extend_from_slice(key.as_bytes());
+        }
+        bytes
+    }
+
+    /// Parses a policy produced by `to_bytes`. Only the canonical encoding is accepted, so a
+    /// policy has exactly one valid byte representation and hash.
+    ///
+    /// # Returns
+    /// * `Err(CryptoError::InvalidMultisigPolicy)` if the bytes are malformed, not canonical
+    ///   (keys unsorted or repeated), or describe an invalid policy.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CryptoError> {
+        let malformed = |reason: &str| CryptoError::InvalidMultisigPolicy(reason.to_string());
+        if bytes.len() < MULTISIG_POLICY_HEADER_BYTES || bytes[..4] != MULTISIG_POLICY_MAGIC {
+            return Err(malformed("not a multisig policy"));
+        }
+        if bytes[4] != MULTISIG_POLICY_VERSION {
+            return Err(CryptoError::InvalidMultisigPolicy(format!("unsupported version {}", bytes[4])));
+        }
+        let threshold = bytes[5] as usize;
+        let count = bytes[6] as usize;
+        let key_bytes = &bytes[MULTISIG_POLICY_HEADER_BYTES..];
+        if key_bytes.len() != count * SIGNING_PUBLIC_KEY_BYTES {
+            return Err(malformed("key count does not match length"));
+        }
+
+        let mut keys = Vec::with_capacity(count);
+        for chunk in key_bytes.chunks_exact(SIGNING_PUBLIC_KEY_BYTES) {
+            let array: [u8; SIGNING_PUBLIC_KEY_BYTES] = chunk.try_into().expect("chunk length is fixed");
+            keys.push(SigningPublicKey::try_from_bytes(&array)?);
+        }
+        if keys.windows(2).any(|pair| pair[0].as_bytes() >= pair[1].as_bytes()) {
+            return Err(malformed("keys are not in canonical order"));
+        }
+        Self::new(keys, threshold)
+    }
+
+    // SHA3-256 of the canonical encoding; identifies the policy in records and approvals
+    pub fn policy_hash(&self) -> Hash {
+        hash_data(&self.to_bytes())
+    }
+}
+
+// Accumulates signatures over one message from the signers of a policy, checking each as it
+// arrives. Signatures from keys outside the policy or that fail to verify are rejected; a
+// second signature from a key that has already signed is ignored.
+#[derive(Debug, Clone)]
+pub struct SignatureCollector {
+    policy: MultisigPolicy,
+    message: Vec<u8>,
+    // Indexed like `policy.keys()`
+    signatures: Vec<Option<Signature>>,
+}
+
+impl SignatureCollector {
+    pub fn new(policy: &MultisigPolicy, message: &[u8]) -> Self {
+        Self {
+            policy: policy.clone(),
+            message: message.to_vec(),
+            signatures: vec![None; policy.keys.len()],
+        }
+    }
+
+    /// Adds one signer's signature over the collector's message.
+    ///
+    /// # Returns
+    /// * `Ok(true)` if this is the first valid signature from `public_key`, `Ok(false)` if the key
+    ///   had already signed.
+    /// * `Err(CryptoError::SignerNotInPolicy)` if the key is not authorized by the policy.
+    /// * `Err(CryptoError::SignatureVerificationFailed)` if the signature is invalid.
+    pub fn add(&mut self, public_key: &SigningPublicKey, signature: &Signature) -> Result<bool, CryptoError> {
+        let position = self.policy.position(public_key).ok_or(CryptoError::SignerNotInPolicy)?;
+        verify_with_mode(public_key, &self.message, signature, VerificationMode::Strict)?;
+        if self.signatures[position].is_some() {
+            return Ok(false);
+        }
+        self.signatures[position] = Some(signature.clone());
+        Ok(true)
+    }
+
+    // Number of distinct authorized keys that have signed so far
+    pub fn signer_count(&self) -> usize {
+        self.signatures.iter().filter(|s| s.is_some()).count()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.signer_count() >= self.policy.threshold()
+    }
+
+    /// Returns the collected signatures, in policy key order, once the threshold is met.
+    ///
+    /// # Returns
+    /// * `Err(CryptoError::MultisigThresholdNotMet)` if fewer than m keys have signed.
+    pub fn finish(self) -> Result<Vec<(SigningPublicKey, Signature)>, CryptoError> {
+        if !self.is_complete() {
+            return Err(CryptoError::MultisigThresholdNotMet {
+                required: self.policy.threshold(),
+                valid: self.signer_count(),
+            });
+        }
+        Ok(self
+            .policy
+            .keys
+            .into_iter()
+            .zip(self.signatures)
+            .filter_map(|(key, signature)| signature.map(|signature| (key, signature)))
+            .collect())
+    }
+}
+
+/// Checks that at least `policy.threshold()` distinct authorized keys signed `message`.
+///
+/// Entries from keys outside the policy, entries whose signature doesn't verify, and repeat
+/// entries for the same key are not counted (but don't fail the check on their own).
+///
+/// # Returns
+/// * `Ok(())` if the threshold is met.
+/// * `Err(CryptoError::MultisigThresholdNotMet)` otherwise.
+pub fn verify_policy(
+    policy: &MultisigPolicy,
+    message: &[u8],
+    signatures: &[(SigningPublicKey, Signature)],
+) -> Result<(), CryptoError> {
+    let mut signed = vec![false; policy.keys.len()];
+    for (public_key, signature) in signatures {
+        if let Some(position) = policy.position(public_key)
+            && !signed[position]
+            && verify_with_mode(public_key, message, signature, VerificationMode::Strict).is_ok()
+        {
+            signed[position] = true;
+        }
+    }
+
+    let valid = signed.iter().filter(|s| **s).count();
+    if valid >= policy.threshold() {
+        Ok(())
+    } else {
+        Err(CryptoError::MultisigThresholdNotMet { required: policy.threshold(), valid })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate_signing_keypair, sign, SigningSecretKey};
+
+    fn signers(n: usize) -> Vec<(SigningSecretKey, SigningPublicKey)> {
+        (0..n).map(|_| generate_signing_keypair()).collect()
+    }
+
+    fn policy_for(signers: &[(SigningSecretKey, SigningPublicKey)], threshold: usize) -> MultisigPolicy {
+        MultisigPolicy::new(signers.iter().map(|(_, pk)| pk.clone()).collect(), threshold).unwrap()
+    }
+
+    #[test]
+    fn test_policy_validation() {
+        let keys: Vec<SigningPublicKey> = signers(3).into_iter().map(|(_, pk)| pk).collect();
+        assert!(MultisigPolicy::new(keys.clone(), 0).is_err());
+        assert!(MultisigPolicy::new(keys.clone(), 4).is_err());
+        assert!(MultisigPolicy::new(Vec::new(), 1).is_err());
+
+        let mut duplicated = keys.clone();
+        duplicated.push(keys[0].clone());
+        assert!(matches!(
+            MultisigPolicy::new(duplicated, 2),
+            Err(CryptoError::InvalidMultisigPolicy(_))
+        ));
+    }
+
+    #[test]
+    fn test_policy_rejects_weak_keys() {
+        let mut identity = [0u8; SIGNING_PUBLIC_KEY_BYTES];
+        identity[0] = 1;
+        let weak = SigningPublicKey::try_from_bytes(&identity).unwrap();
+        let mut keys: Vec<SigningPublicKey> = signers(2).into_iter().map(|(_, pk)| pk).collect();
+        keys.push(weak);
+        assert_eq!(MultisigPolicy::new(keys, 1).unwrap_err(), CryptoError::WeakPublicKey);
+    }
+
+    #[test]
+    fn test_policy_encoding_is_canonical() {
+        let keys: Vec<SigningPublicKey> = signers(4).into_iter().map(|(_, pk)| pk).collect();
+        let policy = MultisigPolicy::new(keys.clone(), 3).unwrap();
+        let mut reversed = keys.clone();
+        reversed.reverse();
+        let reordered = MultisigPolicy::new(reversed, 3).unwrap();
+
+        assert_eq!(policy.to_bytes(), reordered.to_bytes());
+        assert_eq!(policy.policy_hash(), reordered.policy_hash());
+        assert_ne!(policy.policy_hash(), MultisigPolicy::new(keys, 2).unwrap().policy_hash());
+
+        let bytes = policy.to_bytes();
+        assert_eq!(bytes.len(), MULTISIG_POLICY_HEADER_BYTES + 4 * SIGNING_PUBLIC_KEY_BYTES);
+        assert_eq!(MultisigPolicy::from_bytes(&bytes).unwrap(), policy);
+
+        // Swapping two keys gives the same set but a non-canonical encoding
+        let mut swapped = bytes.clone();
+        let first = MULTISIG_POLICY_HEADER_BYTES;
+        let second = first + SIGNING_PUBLIC_KEY_BYTES;
+        let (a, b) = swapped.split_at_mut(second);
+        a[first..].swap_with_slice(&mut b[..SIGNING_PUBLIC_KEY_BYTES]);
+        assert!(MultisigPolicy::from_bytes(&swapped).is_err());
+
+        assert!(MultisigPolicy::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        let mut wrong_version = bytes.clone();
+        wrong_version[4] = 2;
+        assert!(MultisigPolicy::from_bytes(&wrong_version).is_err());
+    }
+
+    #[test]
+    fn test_collector_reaches_threshold() {
+        let signers = signers(3);
+        let policy = policy_for(&signers, 2);
+        let message = b"rotate org signing key";
+        let mut collector = SignatureCollector::new(&policy, message);
+
+        let first = sign(&signers[0].0, message).unwrap();
+        assert!(collector.add(&signers[0].1, &first).unwrap());
+        assert!(!collector.is_complete());
+        // The same signer again doesn't count twice
+        assert!(!collector.add(&signers[0].1, &first).unwrap());
+        assert_eq!(collector.signer_count(), 1);
+        assert_eq!(
+            collector.clone().finish().unwrap_err(),
+            CryptoError::MultisigThresholdNotMet { required: 2, valid: 1 }
+        );
+
+        let second = sign(&signers[2].0, message).unwrap();
+        assert!(collector.add(&signers[2].1, &second).unwrap());
+        assert!(collector.is_complete());
+
+        let collected = collector.finish().unwrap();
+        assert_eq!(collected.len(), 2);
+        assert!(verify_policy(&policy, message, &collected).is_ok());
+    }
+
+    #[test]
+    fn test_collector_rejects_outsiders_and_bad_signatures() {
+        let signers = signers(2);
+        let policy = policy_for(&signers, 1);
+        let mut collector = SignatureCollector::new(&policy, b"message");
+
+        let (outsider_sk, outsider_pk) = generate_signing_keypair();
+        let outsider_sig = sign(&outsider_sk, b"message").unwrap();
+        assert_eq!(collector.add(&outsider_pk, &outsider_sig).unwrap_err(), CryptoError::SignerNotInPolicy);
+
+        let wrong_message = sign(&signers[0].0, b"other message").unwrap();
+        assert_eq!(
+            collector.add(&signers[0].1, &wrong_message).unwrap_err(),
+            CryptoError::SignatureVerificationFailed
+        );
+        assert_eq!(collector.signer_count(), 0);
+    }
+
+    #[test]
+    fn test_verify_policy_counts_distinct_authorized_signers() {
+        let signers = signers(3);
+        let policy = policy_for(&signers, 2);
+        let message = b"approve payout";
+        let sig0 = sign(&signers[0].0, message).unwrap();
+        let sig1 = sign(&signers[1].0, message).unwrap();
+        let (outsider_sk, outsider_pk) = generate_signing_keypair();
+        let outsider_sig = sign(&outsider_sk, message).unwrap();
+        let forged = sign(&signers[2].0, b"something else").unwrap();
+
+        // One signer repeated, an outsider and a bad signature still only make one
+        let not_enough = vec![
+            (signers[0].1.clone(), sig0.clone()),
+            (signers[0].1.clone(), sig0.clone()),
+            (outsider_pk, outsider_sig),
+            (signers[2].1.clone(), forged),
+        ];
+        assert_eq!(
+            verify_policy(&policy, message, &not_enough).unwrap_err(),
+            CryptoError::MultisigThresholdNotMet { required: 2, valid: 1 }
+        );
+
+        let mut enough = not_enough;
+        enough.push((signers[1].1.clone(), sig1));
+        assert!(verify_policy(&policy, message, &enough).is_ok());
+        assert!(verify_policy(&policy, b"different message", &enough).is_err());
+    }
+}
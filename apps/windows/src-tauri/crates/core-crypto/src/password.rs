@@ -0,0 +1,341 @@
+// --- Password-Based Key Derivation (Argon2id) ---
+//
+// Turns a user password into key material with Argon2id (RFC 9106, version 0x13). Two outputs
+// are supported:
+//   * `derive_key_from_password` produces a `SymKey` for encrypting a vault or backup. The
+//     `Argon2Params` (including the salt) must be stored next to the ciphertext so the same key
+//     can be derived again.
+//   * `hash_password` produces a self-describing PHC string
+//     ($argon2id$v=19$m=...,t=...,p=...$salt$hash) for checking a password with `verify_password`.
+//
+// Serialized parameter layout (all integers big-endian):
+//   version (1) || memory KiB (4) || iterations (4) || parallelism (4) || salt (16)
+
+use crate::{CryptoError, SymKey, SYMMETRIC_KEY_BYTES};
+use argon2::password_hash::{self, PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
+use rand::rngs::OsRng as RandOsRng;
+use rand::RngCore;
+use std::time::{Duration, Instant};
+
+pub const ARGON2_SALT_BYTES: usize = 16;
+pub const ARGON2_PARAMS_VERSION: u8 = 1;
+pub const ARGON2_PARAMS_BYTES: usize = 1 + 3 * 4 + ARGON2_SALT_BYTES;
+
+// Upper bounds on the costs accepted from stored parameters or PHC strings (1 GiB, 64 passes,
+// 16 lanes), so a tampered file can't make the app allocate an arbitrary amount of memory or
+// spin for hours
+pub const ARGON2_MAX_MEMORY_KIB: u32 = 1 << 20;
+pub const ARGON2_MAX_ITERATIONS: u32 = 64;
+pub const ARGON2_MAX_PARALLELISM: u32 = 16;
+
+// OWASP-recommended minimum for Argon2id: 19 MiB, 2 iterations, 1 lane
+const RECOMMENDED_MEMORY_KIB: u32 = 19 * 1024;
+const RECOMMENDED_ITERATIONS: u32 = 2;
+const RECOMMENDED_PARALLELISM: u32 = 1;
+
+// Argon2id cost parameters plus the salt. None of the fields are secret.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Argon2Params {
+    pub salt: [u8; ARGON2_SALT_BYTES],
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Argon2Params {
+    /// Creates a parameter set with a fresh random salt.
+    ///
+    /// # Returns
+    /// * `Err(CryptoError::InvalidKdfParameters)` if the costs are outside what Argon2 (or the
+    ///   `ARGON2_MAX_*` bounds) allows, e.g. zero iterations or less than 8 KiB per lane.
+    pub fn new(memory_kib: u32, iterations: u32, parallelism: u32) -> Result<Self, CryptoError> {
+        let mut salt = [0u8; ARGON2_SALT_BYTES];
+        RandOsRng.fill_bytes(&mut salt);
+        let params = Argon2Params {
+            salt,
+            memory_kib,
+            iterations,
+            parallelism,
+        };
+        params.validate()?;
+        Ok(params)
+    }
+
+    /// Creates the recommended interactive parameter set (19 MiB, 2 iterations, 1 lane) with a
+    /// fresh random salt. Use `calibrate_argon2_params` to scale costs to the current machine.
+    pub fn recommended() -> Self {
+        Self::new(RECOMMENDED_MEMORY_KIB, RECOMMENDED_ITERATIONS, RECOMMENDED_PARALLELISM)
+            .expect("recommended Argon2 parameters are valid")
+    }
+
+    // Checks the costs and builds the argon2 crate's parameter type
+    fn validate(&self) -> Result<Params, CryptoError> {
+        check_cost_limits(self.memory_kib, self.iterations, self.parallelism)
+            .map_err(CryptoError::InvalidKdfParameters)?;
+        Params::new(
+            self.memory_kib,
+            self.iterations,
+            self.parallelism,
+            Some(SYMMETRIC_KEY_BYTES),
+        )
+        .map_err(|e| CryptoError::InvalidKdfParameters(e.to_string()))
+    }
+
+    fn hasher(&self) -> Result<Argon2<'static>, CryptoError> {
+        Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, self.validate()?))
+    }
+
+    pub fn to_bytes(&self) -> [u8; ARGON2_PARAMS_BYTES] {
+        let mut bytes = [0u8; ARGON2_PARAMS_BYTES];
+        bytes[0] = ARGON2_PARAMS_VERSION;
+        bytes[1..5].copy_from_slice(&self.memory_kib.to_be_bytes());
+        bytes[5..9].copy_from_slice(&self.iterations.to_be_bytes());
+        bytes[9..13].copy_from_slice(&self.parallelism.to_be_bytes());
+        bytes[13..].copy_from_slice(&self.salt);
+        bytes
+    }
+
+    /// Parses parameters written by `to_bytes` and validates the costs.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CryptoError> {
+        if bytes.len() != ARGON2_PARAMS_BYTES {
+            return Err(CryptoError::InvalidKdfParameters(format!(
+                "expected {} bytes, got {}",
+                ARGON2_PARAMS_BYTES,
+                bytes.len()
+            )));
+        }
+        if bytes[0] != ARGON2_PARAMS_VERSION {
+            return Err(CryptoError::InvalidKdfParameters(format!(
+                "unsupported parameter version {}",
+                bytes[0]
+            )));
+        }
+        let read_u32 = |offset: usize| {
+            u32::from_be_bytes(bytes[offset..offset + 4].try_into().expect("slice has 4 bytes"))
+        };
+        let params = Argon2Params {
+            memory_kib: read_u32(1),
+            iterations: read_u32(5),
+            parallelism: read_u32(9),
+            salt: bytes[13..].try_into().expect("slice has ARGON2_SALT_BYTES length"),
+        };
+        params.validate()?;
+        Ok(params)
+    }
+}
+
+// Shared by stored parameters and PHC strings; the argon2 crate checks the lower bounds
+fn check_cost_limits(memory_kib: u32, iterations: u32, parallelism: u32) -> Result<(), String> {
+    if memory_kib > ARGON2_MAX_MEMORY_KIB {
+        return Err(format!(
+            "memory cost {} KiB exceeds the maximum of {} KiB",
+            memory_kib, ARGON2_MAX_MEMORY_KIB
+        ));
+    }
+    if iterations > ARGON2_MAX_ITERATIONS {
+        return Err(format!(
+            "iteration count {} exceeds the maximum of {}",
+            iterations, ARGON2_MAX_ITERATIONS
+        ));
+    }
+    if parallelism > ARGON2_MAX_PARALLELISM {
+        return Err(format!(
+            "parallelism {} exceeds the maximum of {}",
+            parallelism, ARGON2_MAX_PARALLELISM
+        ));
+    }
+    Ok(())
+}
+
+/// Derives a 32-byte symmetric key from `password` with Argon2id.
+/// The same password and parameters (including the salt) always give the same key.
+pub fn derive_key_from_password(
+    password: &[u8],
+    params: &Argon2Params,
+) -> Result<SymKey, CryptoError> {
+    let mut key = SymKey::from_bytes([0u8; SYMMETRIC_KEY_BYTES]);
+    params
+        .hasher()?
+        .hash_password_into(password, &params.salt, &mut key.0)
+        .map_err(|e| CryptoError::KeyDerivationError(format!("Argon2id failed: {}", e)))?;
+    Ok(key)
+}
+
+/// Hashes `password` into a PHC string that records the algorithm, version, costs and salt,
+/// so `verify_password` needs nothing else to check it.
+pub fn hash_password(password: &[u8], params: &Argon2Params) -> Result<String, CryptoError> {
+    let salt = SaltString::encode_b64(&params.salt)
+        .map_err(|e| CryptoError::InvalidKdfParameters(format!("Invalid salt: {}", e)))?;
+    params
+        .hasher()?
+        .hash_password(password, &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| CryptoError::KeyDerivationError(format!("Argon2id failed: {}", e)))
+}
+
+/// Checks `password` against a PHC string produced by `hash_password`.
+/// The comparison of the hash output is constant-time.
+///
+/// # Returns
+/// * `Ok(())` if the password matches.
+/// * `Err(CryptoError::PasswordVerificationFailed)` if it doesn't.
+/// * `Err(CryptoError::InvalidPasswordHash)` if the string is malformed, isn't Argon2id, or
+///   asks for more than the `ARGON2_MAX_*` bounds allow.
+pub fn verify_password(password: &[u8], phc: &str) -> Result<(), CryptoError> {
+    let hash = PasswordHash::new(phc).map_err(|e| CryptoError::InvalidPasswordHash(e.to_string()))?;
+    if hash.algorithm != argon2::ARGON2ID_IDENT {
+        return Err(CryptoError::InvalidPasswordHash(format!(
+            "unsupported algorithm {}",
+            hash.algorithm
+        )));
+    }
+    let params = Params::try_from(&hash).map_err(|e| CryptoError::InvalidPasswordHash(e.to_string()))?;
+    check_cost_limits(params.m_cost(), params.t_cost(), params.p_cost())
+        .map_err(CryptoError::InvalidPasswordHash)?;
+
+    Argon2::default()
+        .verify_password(password, &hash)
+        .map_err(|e| match e {
+            password_hash::Error::Password => CryptoError::PasswordVerificationFailed,
+            other => CryptoError::InvalidPasswordHash(other.to_string()),
+        })
+}
+
+/// Picks Argon2id parameters that take roughly `target` to evaluate on the current machine.
+///
+/// Memory and parallelism are fixed by the caller (memory is the main defence against GPU
+/// attacks, so choose as much as the slowest supported device can afford); the iteration count
+/// is scaled from a single timed run and clamped to `1..=ARGON2_MAX_ITERATIONS`.
+/// The returned parameters carry a fresh random salt.
+pub fn calibrate_argon2_params(
+    target: Duration,
+    memory_kib: u32,
+    parallelism: u32,
+) -> Result<Argon2Params, CryptoError> {
+    let mut params = Argon2Params::new(memory_kib, 1, parallelism)?;
+
+    let start = Instant::now();
+    derive_key_from_password(b"argon2id calibration", &params)?;
+    let per_iteration = start.elapsed().as_nanos().max(1);
+
+    params.iterations = (target.as_nanos() / per_iteration)
+        .clamp(1, ARGON2_MAX_ITERATIONS as u128) as u32;
+    Ok(params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Small costs keep the tests fast; real callers should use recommended() or calibration
+    fn test_params() -> Argon2Params {
+        Argon2Params::new(64, 1, 1).unwrap()
+    }
+
+    #[test]
+    fn test_derive_key_deterministic() {
+        let params = test_params();
+        let key1 = derive_key_from_password(b"correct horse", &params).unwrap();
+        let key2 = derive_key_from_password(b"correct horse", &params).unwrap();
+        assert_eq!(key1, key2);
+
+        // Different password, salt or cost must give a different key
+        assert_ne!(key1, derive_key_from_password(b"battery staple", &params).unwrap());
+        let other_salt = Argon2Params { salt: [9u8; ARGON2_SALT_BYTES], ..params.clone() };
+        assert_ne!(key1, derive_key_from_password(b"correct horse", &other_salt).unwrap());
+        let more_iterations = Argon2Params { iterations: 2, ..params };
+        assert_ne!(key1, derive_key_from_password(b"correct horse", &more_iterations).unwrap());
+    }
+
+    #[test]
+    fn test_params_roundtrip() {
+        let params = Argon2Params::new(1024, 3, 2).unwrap();
+        let bytes = params.to_bytes();
+        assert_eq!(bytes[0], ARGON2_PARAMS_VERSION);
+        assert_eq!(Argon2Params::from_bytes(&bytes).unwrap(), params);
+
+        // Fresh parameter sets get fresh salts
+        assert_ne!(params.salt, Argon2Params::new(1024, 3, 2).unwrap().salt);
+    }
+
+    #[test]
+    fn test_params_rejects_invalid() {
+        assert!(matches!(Argon2Params::new(64, 0, 1), Err(CryptoError::InvalidKdfParameters(_))));
+        assert!(matches!(Argon2Params::new(64, 1, 0), Err(CryptoError::InvalidKdfParameters(_))));
+        assert!(matches!(
+            Argon2Params::new(ARGON2_MAX_MEMORY_KIB + 1, 1, 1),
+            Err(CryptoError::InvalidKdfParameters(_))
+        ));
+
+        let bytes = test_params().to_bytes();
+        let mut wrong_version = bytes;
+        wrong_version[0] = 2;
+        assert!(matches!(Argon2Params::from_bytes(&wrong_version), Err(CryptoError::InvalidKdfParameters(_))));
+        assert!(matches!(Argon2Params::from_bytes(&bytes[1..]), Err(CryptoError::InvalidKdfParameters(_))));
+        let mut huge_memory = bytes;
+        huge_memory[1..5].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(matches!(Argon2Params::from_bytes(&huge_memory), Err(CryptoError::InvalidKdfParameters(_))));
+        let mut huge_iterations = bytes;
+        huge_iterations[5..9].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(matches!(Argon2Params::from_bytes(&huge_iterations), Err(CryptoError::InvalidKdfParameters(_))));
+        let mut huge_parallelism = bytes;
+        huge_parallelism[1..5].copy_from_slice(&ARGON2_MAX_MEMORY_KIB.to_be_bytes());
+        huge_parallelism[9..13].copy_from_slice(&(ARGON2_MAX_PARALLELISM + 1).to_be_bytes());
+        assert!(matches!(Argon2Params::from_bytes(&huge_parallelism), Err(CryptoError::InvalidKdfParameters(_))));
+        assert!(Argon2Params::new(64, ARGON2_MAX_ITERATIONS + 1, 1).is_err());
+    }
+
+    #[test]
+    fn test_hash_and_verify_password() {
+        let params = test_params();
+        let phc = hash_password(b"hunter2", &params).unwrap();
+        assert!(phc.starts_with("$argon2id$v=19$m=64,t=1,p=1$"));
+
+        assert!(verify_password(b"hunter2", &phc).is_ok());
+        assert_eq!(verify_password(b"hunter3", &phc).unwrap_err(), CryptoError::PasswordVerificationFailed);
+    }
+
+    #[test]
+    fn test_phc_hash_matches_derived_key() {
+        // The PHC hash field is the same Argon2id output as derive_key_from_password
+        let params = test_params();
+        let phc = hash_password(b"hunter2", &params).unwrap();
+        let hash = PasswordHash::new(&phc).unwrap();
+        let key = derive_key_from_password(b"hunter2", &params).unwrap();
+        assert_eq!(hash.hash.unwrap().as_bytes(), key.expose_secret());
+    }
+
+    #[test]
+    fn test_verify_password_rejects_bad_hashes() {
+        let phc = hash_password(b"hunter2", &test_params()).unwrap();
+
+        let argon2i = phc.replacen("$argon2id$", "$argon2i$", 1);
+        assert!(matches!(verify_password(b"hunter2", &argon2i), Err(CryptoError::InvalidPasswordHash(_))));
+
+        let huge_memory = phc.replacen("m=64,", &format!("m={},", ARGON2_MAX_MEMORY_KIB + 1), 1);
+        assert!(matches!(verify_password(b"hunter2", &huge_memory), Err(CryptoError::InvalidPasswordHash(_))));
+        let huge_iterations = phc.replacen(",t=1,", &format!(",t={},", u32::MAX), 1);
+        assert!(matches!(verify_password(b"hunter2", &huge_iterations), Err(CryptoError::InvalidPasswordHash(_))));
+        let huge_parallelism = phc.replacen(
+            "m=64,t=1,p=1$",
+            &format!("m={},t=1,p={}$", ARGON2_MAX_MEMORY_KIB, ARGON2_MAX_PARALLELISM + 1),
+            1,
+        );
+        assert!(matches!(verify_password(b"hunter2", &huge_parallelism), Err(CryptoError::InvalidPasswordHash(_))));
+
+        assert!(matches!(verify_password(b"hunter2", "not a phc string"), Err(CryptoError::InvalidPasswordHash(_))));
+    }
+
+    #[test]
+    fn test_calibrate_argon2_params() {
+        let params = calibrate_argon2_params(Duration::from_millis(20), 256, 1).unwrap();
+        assert_eq!(params.memory_kib, 256);
+        assert_eq!(params.parallelism, 1);
+        assert!((1..=ARGON2_MAX_ITERATIONS).contains(&params.iterations));
+        assert!(derive_key_from_password(b"pw", &params).is_ok());
+
+        // A zero target still yields a usable parameter set
+        assert_eq!(calibrate_argon2_params(Duration::ZERO, 256, 1).unwrap().iterations, 1);
+    }
+}
@@ -0,0 +1,179 @@
+// --- Prehashed Signing (Ed25519ph, RFC 8032 section 5.1) ---
+//
+// Ed25519 (`sign`) needs the whole message in memory, because the message is hashed twice.
+// Ed25519ph signs the SHA-512 digest of the message instead, so the message can be fed through
+// an incremental hasher chunk by chunk and files of any size can be signed while streaming.
+//
+// Ed25519ph signatures are domain-separated from plain Ed25519 by the RFC's dom2 prefix: a
+// prehashed signature never verifies with `verify`, and vice versa.
+
+use crate::{CryptoError, Signature, SigningPublicKey, SigningSecretKey};
+use ed25519_dalek::{Signature as EdSignature, SigningKey, VerifyingKey};
+use sha2::{Digest, Sha512};
+use std::io::{self, Read};
+
+// Bytes read per `update_reader` call to the underlying reader (64 KiB)
+const PREHASH_READ_CHUNK_BYTES: usize = 64 * 1024;
+
+// Incremental SHA-512 hasher for Ed25519ph. Feed it the message with `update`/`update_reader`,
+// then hand it to `sign_prehashed` or `verify_prehashed`.
+#[derive(Clone, Default)]
+pub struct Ed25519phHasher {
+    hasher: Sha512,
+    bytes_hashed: u64,
+}
+
+impl Ed25519phHasher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.hasher.update(data);
+        self.bytes_hashed += data.len() as u64;
+    }
+
+    /// Hashes everything readable from `reader` without holding it in memory.
+    ///
+    /// # Returns
+    /// * `Ok(u64)` with the number of bytes read from `reader`.
+    /// * `Err(CryptoError::IoError)` if reading fails.
+    pub fn update_reader<R: Read>(&mut self, reader: &mut R) -> Result<u64, CryptoError> {
+        let mut buf = vec![0u8; PREHASH_READ_CHUNK_BYTES];
+        let mut total = 0u64;
+        loop {
+            match reader.read(&mut buf) {
+                Ok(0) => return Ok(total),
+                Ok(n) => {
+                    self.update(&buf[..n]);
+                    total += n as u64;
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    // Total number of message bytes fed in so far
+    pub fn bytes_hashed(&self) -> u64 {
+        self.bytes_hashed
+    }
+}
+
+/// Signs the message fed into `hasher` with Ed25519ph (empty context).
+pub fn sign_prehashed(
+    secret_key: &SigningSecretKey,
+    hasher: Ed25519phHasher,
+) -> Result<Signature, CryptoError> {
+    let signing_key = SigningKey::from_bytes(&secret_key.0);
+    let signature = signing_key
+        .sign_prehashed(hasher.hasher, None)
+        .map_err(|e| CryptoError::InternalError(e.to_string()))?;
+    Ok(signature.into())
+}
+
+/// Verifies an Ed25519ph signature over the message fed into `hasher`. Verification is always
+/// strict (see `VerificationMode`).
+///
+/// # Returns
+/// * `Ok(())` if the signature is valid.
+/// * `Err(CryptoError::WeakPublicKey)` if the public key is small-order.
+/// * `Err(CryptoError::SignatureVerificationFailed)` if it is invalid or is a plain Ed25519
+///   signature.
+pub fn verify_prehashed(
+    public_key: &SigningPublicKey,
+    hasher: Ed25519phHasher,
+    signature: &Signature,
+) -> Result<(), CryptoError> {
+    let verifying_key = VerifyingKey::try_from(public_key)?;
+    let ed_signature = <EdSignature as TryFrom<&[u8]>>::try_from(&signature.0)?;
+    if verifying_key.is_weak() {
+        return Err(CryptoError::WeakPublicKey);
+    }
+
+    verifying_key
+        .verify_prehashed_strict(hasher.hasher, None, &ed_signature)
+        .map_err(|_| CryptoError::SignatureVerificationFailed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate_signing_keypair, sign, verify, SIGNATURE_BYTES, SIGNING_PUBLIC_KEY_BYTES};
+
+    fn hasher_for(message: &[u8]) -> Ed25519phHasher {
+        let mut hasher = Ed25519phHasher::new();
+        hasher.update(message);
+        hasher
+    }
+
+    // RFC 8032 section 7.3, "TEST abc"
+    #[test]
+    fn test_ed25519ph_rfc8032_vector() {
+        let secret: [u8; 32] = hex::decode("833fe62409237b9d62ec77587520911e9a759cec1d19755b7da901b96dca3d42")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let public: [u8; SIGNING_PUBLIC_KEY_BYTES] =
+            hex::decode("ec172b93ad5e563bf4932c70e1245034c35467ef2efd4d64ebf819683467e2bf")
+                .unwrap()
+                .try_into()
+                .unwrap();
+        let expected: [u8; SIGNATURE_BYTES] = hex::decode(
+            "98a70222f0b8121aa9d30f813d683f809e462b469c7ff87639499bb94e6dae41\
+             31f85042463c2a355a2003d062adf5aaa10b8c61e636062aaad11c2a26083406",
+        )
+        .unwrap()
+        .try_into()
+        .unwrap();
+
+        let secret_key = SigningSecretKey::from_bytes(secret);
+        let public_key = SigningPublicKey::try_from_bytes(&public).unwrap();
+        let signature = sign_prehashed(&secret_key, hasher_for(b"abc")).unwrap();
+        assert_eq!(signature.as_bytes(), &expected);
+        assert!(verify_prehashed(&public_key, hasher_for(b"abc"), &signature).is_ok());
+    }
+
+    #[test]
+    fn test_chunked_updates_match_single_update() {
+        let (secret_key, public_key) = generate_signing_keypair();
+        let message: Vec<u8> = (0..200_000u32).map(|i| i as u8).collect();
+
+        let mut chunked = Ed25519phHasher::new();
+        for chunk in message.chunks(4096 + 7) {
+            chunked.update(chunk);
+        }
+        assert_eq!(chunked.bytes_hashed(), message.len() as u64);
+
+        let signature = sign_prehashed(&secret_key, chunked).unwrap();
+        assert!(verify_prehashed(&public_key, hasher_for(&message), &signature).is_ok());
+    }
+
+    #[test]
+    fn test_update_reader() {
+        let (secret_key, public_key) = generate_signing_keypair();
+        let message = vec![0x5au8; 3 * PREHASH_READ_CHUNK_BYTES + 123];
+
+        let mut hasher = Ed25519phHasher::new();
+        let read = hasher.update_reader(&mut message.as_slice()).unwrap();
+        assert_eq!(read, message.len() as u64);
+
+        let signature = sign_prehashed(&secret_key, hasher).unwrap();
+        assert!(verify_prehashed(&public_key, hasher_for(&message), &signature).is_ok());
+        assert_eq!(
+            verify_prehashed(&public_key, hasher_for(&message[1..]), &signature).unwrap_err(),
+            CryptoError::SignatureVerificationFailed
+        );
+    }
+
+    #[test]
+    fn test_prehashed_and_plain_signatures_are_separate() {
+        let (secret_key, public_key) = generate_signing_keypair();
+
+        let prehashed = sign_prehashed(&secret_key, hasher_for(b"document")).unwrap();
+        assert!(verify(&public_key, b"document", &prehashed).is_err());
+
+        let plain = sign(&secret_key, b"document").unwrap();
+        assert!(verify_prehashed(&public_key, hasher_for(b"document"), &plain).is_err());
+    }
+}
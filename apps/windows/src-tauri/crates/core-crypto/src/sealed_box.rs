@@ -0,0 +1,153 @@
+// --- Anonymous Sealed Boxes (X25519 + HKDF-SHA256 + ChaCha20-Poly1305) ---
+//
+// Encrypts a message to a recipient's `KeyExchangePublicKey` without any interaction and without
+// revealing who sent it. Each message uses a fresh ephemeral X25519 key pair:
+//
+//   shared = X25519(ephemeral_secret, recipient_public)
+//   key    = HKDF-SHA256(ikm = shared, salt = ephemeral_public || recipient_public, info = "sealed-box")
+//   sealed = ephemeral_public (32) || ChaCha20-Poly1305(key, nonce = 0, plaintext)
+//
+// Binding both public keys into the KDF ties the ciphertext to this exact recipient. Because the
+// derived key is never reused, the all-zero nonce is safe.
+
+use crate::{
+    decrypt_symmetric, derive_hkdf_output, encrypt_symmetric, generate_key_exchange_keypair,
+    key_exchange, CryptoError, KeyExchangePublicKey, KeyExchangeSecretKey, Nonce, SymKey,
+    KEY_EXCHANGE_PUBLIC_KEY_BYTES, NONCE_BYTES, SYMMETRIC_KEY_BYTES,
+};
+
+// Bytes added to the plaintext: the ephemeral public key plus the Poly1305 tag
+pub const SEALED_BOX_OVERHEAD_BYTES: usize = KEY_EXCHANGE_PUBLIC_KEY_BYTES + 16;
+
+const SEALED_BOX_INFO: &[u8] = b"sealed-box";
+const SEALED_BOX_NONCE: Nonce = [0u8; NONCE_BYTES];
+
+// Derives the one-time symmetric key from the DH output and both public keys
+fn derive_sealed_box_key(
+    shared_secret: &[u8],
+    ephemeral_public: &KeyExchangePublicKey,
+    recipient_public: &KeyExchangePublicKey,
+) -> Result<SymKey, CryptoError> {
+    let mut salt = [0u8; 2 * KEY_EXCHANGE_PUBLIC_KEY_BYTES];
+    salt[..KEY_EXCHANGE_PUBLIC_KEY_BYTES].copy_from_slice(ephemeral_public.as_bytes());
+    salt[KEY_EXCHANGE_PUBLIC_KEY_BYTES..].copy_from_slice(recipient_public.as_bytes());
+
+    let mut key = SymKey::from_bytes([0u8; SYMMETRIC_KEY_BYTES]);
+    derive_hkdf_output(shared_secret, &salt, SEALED_BOX_INFO, &mut key.0)?;
+    Ok(key)
+}
+
+/// Encrypts `plaintext` so that only the holder of the secret key for `recipient` can read it.
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` with `SEALED_BOX_OVERHEAD_BYTES + plaintext.len()` bytes.
+/// * `Err(CryptoError::KeyExchangeError)` if `recipient` is a low-order (weak) public key.
+pub fn seal(recipient: &KeyExchangePublicKey, plaintext: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    let (ephemeral_secret, ephemeral_public) = generate_key_exchange_keypair();
+    let shared_secret = key_exchange(&ephemeral_secret, recipient)?;
+    let key = derive_sealed_box_key(shared_secret.expose_secret(), &ephemeral_public, recipient)?;
+
+    let ciphertext = encrypt_symmetric(&key, plaintext, &SEALED_BOX_NONCE, None)?;
+
+    let mut sealed = Vec::with_capacity(KEY_EXCHANGE_PUBLIC_KEY_BYTES + ciphertext.len());
+    sealed.extend_from_slice(ephemeral_public.as_bytes());
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+/// Decrypts a sealed box produced by `seal` for the public key belonging to `recipient_secret`.
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` with the plaintext.
+/// * `Err(CryptoError::DecryptionError)` if the box was sealed to a different key, was tampered
+///   with, or is too short to be a sealed box.
+pub fn open(recipient_secret: &KeyExchangeSecretKey, sealed: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    if sealed.len() < SEALED_BOX_OVERHEAD_BYTES {
+        return Err(CryptoError::DecryptionError);
+    }
+    let (ephemeral_bytes, ciphertext) = sealed.split_at(KEY_EXCHANGE_PUBLIC_KEY_BYTES);
+    let ephemeral_public = KeyExchangePublicKey::from_bytes(
+        ephemeral_bytes.try_into().expect("split at public key length"),
+    );
+    let recipient_public = recipient_secret.public_key();
+
+    // A low-order ephemeral key can only come from a forged box
+    let shared_secret =
+        key_exchange(recipient_secret, &ephemeral_public).map_err(|_| CryptoError::DecryptionError)?;
+    let key = derive_sealed_box_key(shared_secret.expose_secret(), &ephemeral_public, &recipient_public)?;
+
+    decrypt_symmetric(&key, ciphertext, &SEALED_BOX_NONCE, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sealed_box_roundtrip() {
+        let (recipient_secret, recipient_public) = generate_key_exchange_keypair();
+        let plaintext = b"content key for bob";
+
+        let sealed = seal(&recipient_public, plaintext).expect("Sealing failed");
+        assert_eq!(sealed.len(), plaintext.len() + SEALED_BOX_OVERHEAD_BYTES);
+
+        let opened = open(&recipient_secret, &sealed).expect("Opening failed");
+        assert_eq!(opened, plaintext);
+    }
+
+    #[test]
+    fn test_sealed_box_is_randomized() {
+        let (_, recipient_public) = generate_key_exchange_keypair();
+        let sealed1 = seal(&recipient_public, b"same message").unwrap();
+        let sealed2 = seal(&recipient_public, b"same message").unwrap();
+        assert_ne!(sealed1, sealed2);
+    }
+
+    #[test]
+    fn test_sealed_box_wrong_recipient() {
+        let (_, recipient_public) = generate_key_exchange_keypair();
+        let (other_secret, _) = generate_key_exchange_keypair();
+
+        let sealed = seal(&recipient_public, b"not for you").unwrap();
+        assert_eq!(open(&other_secret, &sealed).unwrap_err(), CryptoError::DecryptionError);
+    }
+
+    #[test]
+    fn test_sealed_box_tampering() {
+        let (recipient_secret, recipient_public) = generate_key_exchange_keypair();
+        let sealed = seal(&recipient_public, b"integrity matters").unwrap();
+
+        // Tampered ephemeral key
+        let mut tampered = sealed.clone();
+        tampered[0] ^= 0x01;
+        assert_eq!(open(&recipient_secret, &tampered).unwrap_err(), CryptoError::DecryptionError);
+
+        // Tampered ciphertext
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 0x01;
+        assert_eq!(open(&recipient_secret, &tampered).unwrap_err(), CryptoError::DecryptionError);
+
+        // Truncated
+        assert_eq!(
+            open(&recipient_secret, &sealed[..SEALED_BOX_OVERHEAD_BYTES - 1]).unwrap_err(),
+            CryptoError::DecryptionError
+        );
+    }
+
+    #[test]
+    fn test_sealed_box_rejects_weak_recipient_key() {
+        let weak_public = KeyExchangePublicKey::from_bytes([0u8; KEY_EXCHANGE_PUBLIC_KEY_BYTES]);
+        match seal(&weak_public, b"to nobody") {
+            Err(CryptoError::KeyExchangeError(_)) => {}
+            other => panic!("Expected KeyExchangeError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_sealed_box_rejects_weak_ephemeral_key() {
+        let (recipient_secret, _) = generate_key_exchange_keypair();
+        // All-zero ephemeral key followed by a plausible-length ciphertext
+        let forged = vec![0u8; SEALED_BOX_OVERHEAD_BYTES + 4];
+        assert_eq!(open(&recipient_secret, &forged).unwrap_err(), CryptoError::DecryptionError);
+    }
+}
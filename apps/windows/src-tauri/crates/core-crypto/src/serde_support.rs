@@ -0,0 +1,351 @@
+// --- Serde Support (feature "serde") ---
+//
+// Public keys, signatures and the self-describing byte formats (envelopes, wrapped keys,
+// policies, tokens, Argon2 parameters) serialize as lowercase hex strings in human-readable
+// formats (JSON, TOML, ...) and as raw bytes in binary ones. Deserializing goes through the
+// same validating constructors as the rest of the API (`try_from_bytes`, `parse`, `from_bytes`),
+// so an invalid key or a tampered structure fails to deserialize instead of failing later.
+//
+// Secret types deliberately don't implement Serialize/Deserialize, so a key can't end up in a
+// config file, log line or IPC payload just because it sits in a struct that derives Serialize.
+// Serializing one requires wrapping it in `SerdeSecret` at the point where that is intended.
+
+use crate::{
+    Argon2Params, ContentCiphertext, ContentMasterKey, Envelope, KeyExchangePublicKey,
+    KeyExchangeSecretKey, KeyToken, MasterSeed, MultisigPolicy, RootIdentitySecret, Signature,
+    SigningPublicKey, SigningSecretKey, SymKey, TokenClaims, WrappedKey,
+};
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use zeroize::Zeroizing;
+
+fn serialize_bytes<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    if serializer.is_human_readable() {
+        serializer.serialize_str(&Zeroizing::new(hex::encode(bytes)))
+    } else {
+        serializer.serialize_bytes(bytes)
+    }
+}
+
+// Decoded bytes are zeroized on drop, since the same path is used for secrets
+fn deserialize_bytes<'de, D: Deserializer<'de>>(
+    deserializer: D,
+    expecting: &'static str,
+) -> Result<Zeroizing<Vec<u8>>, D::Error> {
+    let visitor = BytesVisitor { expecting };
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_str(visitor)
+    } else {
+        deserializer.deserialize_bytes(visitor)
+    }
+}
+
+struct BytesVisitor {
+    expecting: &'static str,
+}
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Zeroizing<Vec<u8>>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{} as a hex string or bytes", self.expecting)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        hex::decode(value).map(Zeroizing::new).map_err(E::custom)
+    }
+
+    fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<Self::Value, E> {
+        Ok(Zeroizing::new(value.to_vec()))
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, value: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(Zeroizing::new(value))
+    }
+
+    // Some binary formats hand bytes over as a sequence of u8
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bytes = Zeroizing::new(Vec::with_capacity(seq.size_hint().unwrap_or(0)));
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        Ok(bytes)
+    }
+}
+
+fn fixed<const N: usize>(bytes: &[u8], what: &str) -> Result<[u8; N], String> {
+    bytes
+        .try_into()
+        .map_err(|_| format!("invalid {} length {}, expected {}", what, bytes.len(), N))
+}
+
+// Implements Serialize/Deserialize for a type via its byte encoding
+macro_rules! serde_via_bytes {
+    ($ty:ty, $expecting:literal, |$value:ident| $to_bytes:expr, |$bytes:ident| $from_bytes:expr) => {
+        impl Serialize for $ty {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let $value = self;
+                serialize_bytes(&$to_bytes[..], serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                fn parse($bytes: &[u8]) -> Result<$ty, String> {
+                    $from_bytes
+                }
+                let decoded = deserialize_bytes(deserializer, $expecting)?;
+                parse(&decoded).map_err(de::Error::custom)
+            }
+        }
+    };
+}
+
+serde_via_bytes!(SigningPublicKey, "an Ed25519 public key", |key| key.as_bytes(), |bytes| {
+    SigningPublicKey::try_from_bytes(&fixed(bytes, "public key")?).map_err(|e| e.to_string())
+});
+serde_via_bytes!(Signature, "an Ed25519 signature", |signature| signature.as_bytes(), |bytes| {
+    Signature::try_from_bytes(&fixed(bytes, "signature")?).map_err(|e| e.to_string())
+});
+serde_via_bytes!(KeyExchangePublicKey, "an X25519 public key", |key| key.as_bytes(), |bytes| {
+    Ok(KeyExchangePublicKey::from_bytes(fixed(bytes, "public key")?))
+});
+serde_via_bytes!(Envelope, "a ciphertext envelope", |envelope| envelope.to_bytes(), |bytes| {
+    Envelope::parse(bytes).map_err(|e| e.to_string())
+});
+serde_via_bytes!(ContentCiphertext, "a content ciphertext", |ciphertext| ciphertext.to_bytes(), |bytes| {
+    ContentCiphertext::parse(bytes).map_err(|e| e.to_string())
+});
+serde_via_bytes!(WrappedKey, "a wrapped key", |wrapped| wrapped.to_bytes(), |bytes| {
+    WrappedKey::parse(bytes).map_err(|e| e.to_string())
+});
+serde_via_bytes!(Argon2Params, "Argon2 parameters", |params| params.to_bytes(), |bytes| {
+    Argon2Params::from_bytes(bytes).map_err(|e| e.to_string())
+});
+serde_via_bytes!(MultisigPolicy, "a multi-signature policy", |policy| policy.to_bytes(), |bytes| {
+    MultisigPolicy::from_bytes(bytes).map_err(|e| e.to_string())
+});
+serde_via_bytes!(TokenClaims, "key token claims", |claims| claims.to_bytes(), |bytes| {
+    TokenClaims::from_bytes(bytes).map_err(|e| e.to_string())
+});
+serde_via_bytes!(KeyToken, "a key token", |token| token.to_bytes(), |bytes| {
+    KeyToken::from_bytes(bytes).map_err(|e| e.to_string())
+});
+
+mod private {
+    pub trait Sealed {}
+
+    // What `SerdeSecret` serializes; implemented for each secret type and references to it
+    pub trait SecretBytes {
+        fn secret_bytes(&self) -> &[u8];
+    }
+}
+
+use private::SecretBytes;
+
+// Secret types that may be serialized through `SerdeSecret`
+pub trait SerializableSecret: private::Sealed + Sized {
+    #[doc(hidden)]
+    fn from_secret_bytes(bytes: &[u8]) -> Result<Self, String>;
+}
+
+macro_rules! serializable_secret {
+    ($($ty:ident),* $(,)?) => {$(
+        impl private::Sealed for $ty {}
+
+        impl SecretBytes for $ty {
+            fn secret_bytes(&self) -> &[u8] {
+                self.expose_secret()
+            }
+        }
+
+        impl SecretBytes for &$ty {
+            fn secret_bytes(&self) -> &[u8] {
+                self.expose_secret()
+            }
+        }
+
+        impl SerializableSecret for $ty {
+            fn from_secret_bytes(bytes: &[u8]) -> Result<Self, String> {
+                let mut key = $ty::from_bytes(Default::default());
+                if bytes.len() != key.0.len() {
+                    return Err(format!("invalid key length {}, expected {}", bytes.len(), key.0.len()));
+                }
+                key.0.copy_from_slice(bytes);
+                Ok(key)
+            }
+        }
+    )*};
+}
+
+serializable_secret!(SymKey, SigningSecretKey, KeyExchangeSecretKey, RootIdentitySecret, ContentMasterKey);
+
+impl private::Sealed for MasterSeed {}
+
+impl SecretBytes for MasterSeed {
+    fn secret_bytes(&self) -> &[u8] {
+        self.expose_secret()
+    }
+}
+
+impl SecretBytes for &MasterSeed {
+    fn secret_bytes(&self) -> &[u8] {
+        self.expose_secret()
+    }
+}
+
+impl SerializableSecret for MasterSeed {
+    fn from_secret_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.is_empty() {
+            return Err("empty master seed".to_string());
+        }
+        Ok(MasterSeed::from_bytes(bytes.to_vec()))
+    }
+}
+
+// Explicit opt-in to serializing a secret, e.g. for an encrypted backup:
+//   serde_json::to_string(&SerdeSecret(&signing_secret_key))
+//   let SerdeSecret(key) = serde_json::from_str::<SerdeSecret<SigningSecretKey>>(&json)?;
+// Serializes like the public types (hex or raw bytes) and validates the length on deserialize.
+#[derive(Debug)]
+pub struct SerdeSecret<T>(pub T);
+
+impl<T> SerdeSecret<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: SecretBytes> Serialize for SerdeSecret<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_bytes(self.0.secret_bytes(), serializer)
+    }
+}
+
+impl<'de, T: SerializableSecret> Deserialize<'de> for SerdeSecret<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes = deserialize_bytes(deserializer, "secret key material")?;
+        T::from_secret_bytes(&bytes).map(SerdeSecret).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        encrypt_content, generate_key_exchange_keypair, generate_signing_keypair, seal_envelope, sign,
+        wrap_key, SymmetricAlgorithm,
+    };
+    use serde::de::value::{BytesDeserializer, Error as ValueError};
+
+    // Feeds raw bytes to a Deserialize impl the way a binary format would
+    struct BinaryDeserializer<'a>(&'a [u8]);
+
+    impl<'de> Deserializer<'de> for BinaryDeserializer<'_> {
+        type Error = ValueError;
+
+        fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+            BytesDeserializer::<ValueError>::new(self.0).deserialize_any(visitor)
+        }
+
+        fn is_human_readable(&self) -> bool {
+            false
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf option unit unit_struct newtype_struct seq tuple
+            tuple_struct map struct enum identifier ignored_any
+        }
+    }
+
+    fn from_binary<'a, T: Deserialize<'a>>(bytes: &'a [u8]) -> Result<T, ValueError> {
+        T::deserialize(BinaryDeserializer(bytes))
+    }
+
+    #[test]
+    fn test_public_types_use_hex_in_json() {
+        let (secret_key, public_key) = generate_signing_keypair();
+        let signature = sign(&secret_key, b"message").unwrap();
+
+        let json = serde_json::to_string(&public_key).unwrap();
+        assert_eq!(json, format!("\"{}\"", hex::encode(public_key.as_bytes())));
+        assert_eq!(serde_json::from_str::<SigningPublicKey>(&json).unwrap(), public_key);
+
+        let json = serde_json::to_string(&signature).unwrap();
+        assert_eq!(serde_json::from_str::<Signature>(&json).unwrap(), signature);
+
+        let (_, exchange_key) = generate_key_exchange_keypair();
+        let json = serde_json::to_string(&exchange_key).unwrap();
+        assert_eq!(serde_json::from_str::<KeyExchangePublicKey>(&json).unwrap(), exchange_key);
+    }
+
+    #[test]
+    fn test_binary_formats_use_raw_bytes() {
+        let (_, public_key) = generate_signing_keypair();
+        assert_eq!(from_binary::<SigningPublicKey>(public_key.as_bytes()).unwrap(), public_key);
+        assert!(from_binary::<SigningPublicKey>(&public_key.as_bytes()[1..]).is_err());
+        // A hex string is not accepted where raw bytes are expected
+        let hex_bytes = hex::encode(public_key.as_bytes());
+        assert!(from_binary::<SigningPublicKey>(hex_bytes.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_deserialize_validates() {
+        // Not every 32-byte string is a point on the curve
+        let invalid_point = (0u8..=255)
+            .map(|b| [b; 32])
+            .find(|bytes| SigningPublicKey::try_from_bytes(bytes).is_err())
+            .unwrap();
+        let json = format!("\"{}\"", hex::encode(invalid_point));
+        assert!(serde_json::from_str::<SigningPublicKey>(&json).is_err());
+
+        assert!(serde_json::from_str::<SigningPublicKey>("\"abcd\"").is_err());
+        assert!(serde_json::from_str::<SigningPublicKey>("\"not hex\"").is_err());
+        assert!(serde_json::from_str::<Signature>(&format!("\"{}\"", "00".repeat(63))).is_err());
+    }
+
+    #[test]
+    fn test_structured_types_roundtrip_and_validate() {
+        let key = SymKey::from_bytes([3u8; 32]);
+        let envelope = seal_envelope(&key, SymmetricAlgorithm::XChaCha20Poly1305, b"hello", None).unwrap();
+        let json = serde_json::to_string(&envelope).unwrap();
+        assert_eq!(serde_json::from_str::<Envelope>(&json).unwrap().to_bytes(), envelope.to_bytes());
+        assert!(serde_json::from_str::<Envelope>("\"00112233\"").is_err());
+
+        let content = encrypt_content(&RootIdentitySecret::from_bytes([4u8; 32]), b"doc", 2, b"hello").unwrap();
+        let json = serde_json::to_string(&content).unwrap();
+        assert_eq!(serde_json::from_str::<ContentCiphertext>(&json).unwrap(), content);
+
+        let wrapped = wrap_key(&key, &ContentMasterKey::from_bytes([9u8; 32])).unwrap();
+        let json = serde_json::to_string(&wrapped).unwrap();
+        assert_eq!(serde_json::from_str::<WrappedKey>(&json).unwrap().to_bytes(), wrapped.to_bytes());
+
+        let keys = vec![generate_signing_keypair().1, generate_signing_keypair().1];
+        let policy = MultisigPolicy::new(keys, 2).unwrap();
+        let json = serde_json::to_string(&policy).unwrap();
+        assert_eq!(serde_json::from_str::<MultisigPolicy>(&json).unwrap(), policy);
+
+        let params = Argon2Params::new(8 * 1024, 1, 1).unwrap();
+        let json = serde_json::to_string(&params).unwrap();
+        assert_eq!(serde_json::from_str::<Argon2Params>(&json).unwrap().to_bytes(), params.to_bytes());
+    }
+
+    #[test]
+    fn test_secrets_need_explicit_wrapper() {
+        let (secret_key, _) = generate_signing_keypair();
+        let json = serde_json::to_string(&SerdeSecret(&secret_key)).unwrap();
+        assert_eq!(json, format!("\"{}\"", hex::encode(secret_key.expose_secret())));
+
+        let SerdeSecret(restored) = serde_json::from_str::<SerdeSecret<SigningSecretKey>>(&json).unwrap();
+        assert_eq!(restored, secret_key);
+        assert!(serde_json::from_str::<SerdeSecret<SymKey>>("\"0011\"").is_err());
+
+        let seed = MasterSeed::from_bytes(vec![5u8; 64]);
+        let restored = from_binary::<SerdeSecret<MasterSeed>>(seed.expose_secret()).unwrap().into_inner();
+        assert_eq!(restored, seed);
+        assert!(from_binary::<SerdeSecret<MasterSeed>>(&[]).is_err());
+    }
+}
@@ -0,0 +1,180 @@
+// --- Domain-Separated (Contextual) Signing ---
+//
+// Binds every signature to the purpose it was made for, so a signature over a token can't be
+// replayed as a ledger entry or a login response. The signed bytes are
+//
+//   "paynless-signing-context-v1" || label length (1) || label || message
+//
+// where the label comes from the `SigningContext` registry below. The length prefix keeps a
+// label from bleeding into the message (e.g. "ab" + "c..." vs "abc" + "...").
+//
+// The raw `sign` refuses messages that start with the domain, so a raw signature over
+// attacker-chosen bytes can't carry a forged context prefix.
+
+use crate::{
+    sign_unchecked, verify_with_mode, CryptoError, Signature, SigningPublicKey, SigningSecretKey, VerificationMode,
+};
+
+pub(crate) const SIGNING_CONTEXT_DOMAIN: &[u8] = b"paynless-signing-context-v1";
+
+// Registry of signing purposes. Labels are part of every signature made with them and must
+// never change or be reused for a different purpose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SigningContext {
+    // Identity key signing entries on the primary chain (see derive_identity_signing_keypair)
+    PrimaryChainSigning,
+    // Content key tokens (see derive_token_signing_keypair)
+    TransactableKeyToken,
+    // Individual ledger entries
+    LedgerEntry,
+    // Signed content manifests
+    ContentManifest,
+    // Responses to login / authentication challenges
+    LoginChallenge,
+}
+
+impl SigningContext {
+    pub const ALL: [SigningContext; 5] = [
+        SigningContext::PrimaryChainSigning,
+        SigningContext::TransactableKeyToken,
+        SigningContext::LedgerEntry,
+        SigningContext::ContentManifest,
+        SigningContext::LoginChallenge,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SigningContext::PrimaryChainSigning => "primary-chain-signing",
+            SigningContext::TransactableKeyToken => "transactable-key-token",
+            SigningContext::LedgerEntry => "ledger-entry",
+            SigningContext::ContentManifest => "content-manifest",
+            SigningContext::LoginChallenge => "login-challenge",
+        }
+    }
+
+    /// Looks up a context by its registered label.
+    ///
+    /// # Returns
+    /// * `Err(CryptoError::UnknownSigningContext)` if the label isn't in the registry.
+    pub fn from_label(label: &str) -> Result<Self, CryptoError> {
+        SigningContext::ALL
+            .into_iter()
+            .find(|context| context.label() == label)
+            .ok_or_else(|| CryptoError::UnknownSigningContext(label.to_string()))
+    }
+
+    // The exact bytes that get signed for `message` in this context
+    fn signed_bytes(&self, message: &[u8]) -> Vec<u8> {
+        let label = self.label().as_bytes();
+        let mut bytes = Vec::with_capacity(SIGNING_CONTEXT_DOMAIN.len() + 1 + label.len() + message.len());
+        bytes.extend_from_slice(SIGNING_CONTEXT_DOMAIN);
+        bytes.push(u8::try_from(label.len()).expect("registered labels are shorter than 256 bytes"));
+        bytes.extend_from_slice(label);
+        bytes.extend_from_slice(message);
+        bytes
+    }
+}
+
+/// Signs `message` for a specific purpose. The signature only verifies with `verify_with_context`
+/// under the same context.
+pub fn sign_with_context(
+    secret_key: &SigningSecretKey,
+    context: SigningContext,
+    message: &[u8],
+) -> Result<Signature, CryptoError> {
+    sign_unchecked(secret_key, &context.signed_bytes(message))
+}
+
+/// Verifies a signature made with `sign_with_context`. Verification is always strict (see
+/// `VerificationMode`).
+///
+/// # Returns
+/// * `Ok(())` if the signature is valid for this message in this context.
+/// * `Err(CryptoError::WeakPublicKey)` if the public key is small-order.
+/// * `Err(CryptoError::SignatureVerificationFailed)` if it is invalid, was made for a different
+///   context, or is a raw `sign` signature over the message.
+pub fn verify_with_context(
+    public_key: &SigningPublicKey,
+    context: SigningContext,
+    message: &[u8],
+    signature: &Signature,
+) -> Result<(), CryptoError> {
+    verify_with_mode(public_key, &context.signed_bytes(message), signature, VerificationMode::Strict)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate_signing_keypair, sign, verify};
+
+    #[test]
+    fn test_context_sign_verify_roundtrip() {
+        let (secret_key, public_key) = generate_signing_keypair();
+        for context in SigningContext::ALL {
+            let signature = sign_with_context(&secret_key, context, b"payload").unwrap();
+            assert!(verify_with_context(&public_key, context, b"payload", &signature).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_cross_context_signature_rejected() {
+        let (secret_key, public_key) = generate_signing_keypair();
+        let signature = sign_with_context(&secret_key, SigningContext::TransactableKeyToken, b"payload").unwrap();
+
+        for context in SigningContext::ALL {
+            if context == SigningContext::TransactableKeyToken {
+                continue;
+            }
+            assert_eq!(
+                verify_with_context(&public_key, context, b"payload", &signature).unwrap_err(),
+                CryptoError::SignatureVerificationFailed
+            );
+        }
+    }
+
+    #[test]
+    fn test_raw_and_contextual_signatures_are_separate() {
+        let (secret_key, public_key) = generate_signing_keypair();
+
+        // A raw signature doesn't verify in any context...
+        let raw = sign(&secret_key, b"payload").unwrap();
+        assert!(verify_with_context(&public_key, SigningContext::LedgerEntry, b"payload", &raw).is_err());
+
+        // ...and a contextual signature doesn't verify as a raw one
+        let contextual = sign_with_context(&secret_key, SigningContext::LedgerEntry, b"payload").unwrap();
+        assert!(verify(&public_key, b"payload", &contextual).is_err());
+    }
+
+    #[test]
+    fn test_raw_sign_cannot_forge_context_prefix() {
+        let (secret_key, _) = generate_signing_keypair();
+        let forged = SigningContext::TransactableKeyToken.signed_bytes(b"payload");
+        assert_eq!(sign(&secret_key, &forged).unwrap_err(), CryptoError::ReservedSigningDomain);
+        assert_eq!(sign(&secret_key, SIGNING_CONTEXT_DOMAIN).unwrap_err(), CryptoError::ReservedSigningDomain);
+        // Only the full domain is reserved
+        assert!(sign(&secret_key, &SIGNING_CONTEXT_DOMAIN[..8]).is_ok());
+    }
+
+    #[test]
+    fn test_signed_bytes_layout() {
+        let bytes = SigningContext::LedgerEntry.signed_bytes(b"msg");
+        let mut expected = b"paynless-signing-context-v1".to_vec();
+        expected.push(12);
+        expected.extend_from_slice(b"ledger-entry");
+        expected.extend_from_slice(b"msg");
+        assert_eq!(bytes, expected);
+    }
+
+    #[test]
+    fn test_context_labels() {
+        for context in SigningContext::ALL {
+            assert_eq!(SigningContext::from_label(context.label()).unwrap(), context);
+        }
+        assert_eq!(SigningContext::PrimaryChainSigning.label(), "primary-chain-signing");
+        assert_eq!(SigningContext::TransactableKeyToken.label(), "transactable-key-token");
+        assert_eq!(
+            SigningContext::from_label("no-such-context").unwrap_err(),
+            CryptoError::UnknownSigningContext("no-such-context".to_string())
+        );
+    }
+}
@@ -0,0 +1,365 @@
+// --- SLIP-0010 Ed25519 Derivation ---
+//
+// The hierarchical derivation other Ed25519 wallets use, so keys derived from the same mnemonic
+// can be cross-checked against them. It is an alternative to the HKDF hierarchy, not a layer
+// on top of it: the two schemes give unrelated keys for the same seed.
+//   master: I = HMAC-SHA512("ed25519 seed", seed)
+//   child:  I = HMAC-SHA512(chain code, 0x00 || parent key || ser32(index))
+// with the key in the left half of I and the chain code in the right half. Ed25519 only has
+// hardened children, so every path segment must be hardened ("44'", "44h" or "44H").
+
+use crate::{
+    derive_identity_signing_keypair, derive_root_identity_secret, CryptoError, MasterSeed,
+    SigningPublicKey, SigningSecretKey, SIGNING_SECRET_KEY_BYTES,
+};
+use ed25519_dalek::SigningKey;
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+use std::fmt;
+use std::str::FromStr;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+pub const SLIP10_HARDENED_OFFSET: u32 = 0x8000_0000;
+pub const SLIP10_CHAIN_CODE_BYTES: usize = 32;
+
+const SLIP10_ED25519_CURVE_KEY: &[u8] = b"ed25519 seed";
+// BIP-32 seed length limits (128 to 512 bits)
+const SLIP10_MIN_SEED_BYTES: usize = 16;
+const SLIP10_MAX_SEED_BYTES: usize = 64;
+
+// A BIP-32 style path such as "m/44'/501'/0'". Indexes are stored with the hardened offset added.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Slip10Path {
+    indexes: Vec<u32>,
+}
+
+impl Slip10Path {
+    pub fn master() -> Self {
+        Self { indexes: Vec::new() }
+    }
+
+    // Takes indexes that already include the hardened offset
+    pub fn from_indexes(indexes: Vec<u32>) -> Result<Self, CryptoError> {
+        if let Some(index) = indexes.iter().find(|index| **index < SLIP10_HARDENED_OFFSET) {
+            return Err(CryptoError::InvalidDerivationPath(format!(
+                "SLIP-0010 Ed25519 only supports hardened indexes, got {}",
+                index
+            )));
+        }
+        Ok(Self { indexes })
+    }
+
+    pub fn indexes(&self) -> &[u32] {
+        &self.indexes
+    }
+}
+
+impl fmt::Display for Slip10Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("m")?;
+        for index in &self.indexes {
+            write!(f, "/{}'", index - SLIP10_HARDENED_OFFSET)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Slip10Path {
+    type Err = CryptoError;
+
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| CryptoError::InvalidDerivationPath(format!("{}: {}", reason, path));
+        let mut parts = path.split('/');
+        if parts.next() != Some("m") {
+            return Err(invalid("path must start at \"m\""));
+        }
+
+        let indexes = parts
+            .map(|part| {
+                let number = part
+                    .strip_suffix(['\'', 'h', 'H'])
+                    .ok_or_else(|| invalid("SLIP-0010 Ed25519 only supports hardened segments"))?;
+                if number.is_empty() || !number.bytes().all(|b| b.is_ascii_digit()) {
+                    return Err(invalid("invalid path segment"));
+                }
+                number
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|index| *index < SLIP10_HARDENED_OFFSET)
+                    .map(|index| index + SLIP10_HARDENED_OFFSET)
+                    .ok_or_else(|| invalid("path index out of range"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { indexes })
+    }
+}
+
+// A private key and chain code at some point in the tree
+#[derive(Zeroize, ZeroizeOnDrop)]
+pub struct Slip10ExtendedKey {
+    secret_key: [u8; SIGNING_SECRET_KEY_BYTES],
+    chain_code: [u8; SLIP10_CHAIN_CODE_BYTES],
+}
+
+impl fmt::Debug for Slip10ExtendedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Slip10ExtendedKey([REDACTED])")
+    }
+}
+
+impl Slip10ExtendedKey {
+    /// Derives the master key from a BIP-39 seed (the 64-byte output of `mnemonic_to_seed`).
+    ///
+    /// # Returns
+    /// * `Ok(Slip10ExtendedKey)` for the path "m".
+    /// * `Err(CryptoError::KeyDerivationError)` if the seed is shorter than 16 or longer than 64 bytes.
+    pub fn master(seed: &MasterSeed) -> Result<Self, CryptoError> {
+        let seed = seed.expose_secret();
+        if !(SLIP10_MIN_SEED_BYTES..=SLIP10_MAX_SEED_BYTES).contains(&seed.len()) {
+            return Err(CryptoError::KeyDerivationError(format!(
+                "SLIP-0010 seeds must be {} to {} bytes, got {}",
+                SLIP10_MIN_SEED_BYTES,
+                SLIP10_MAX_SEED_BYTES,
+                seed.len()
+            )));
+        }
+        Self::from_hmac(SLIP10_ED25519_CURVE_KEY, &[seed])
+    }
+
+    /// Derives a hardened child. `index` must include the hardened offset.
+    ///
+    /// # Returns
+    /// * `Ok(Slip10ExtendedKey)` for the child.
+    /// * `Err(CryptoError::InvalidDerivationPath)` if `index` is not hardened.
+    pub fn derive_child(&self, index: u32) -> Result<Self, CryptoError> {
+        if index < SLIP10_HARDENED_OFFSET {
+            return Err(CryptoError::InvalidDerivationPath(format!(
+                "SLIP-0010 Ed25519 only supports hardened indexes, got {}",
+                index
+            )));
+        }
+        Self::from_hmac(&self.chain_code, &[&[0u8], &self.secret_key, &index.to_be_bytes()])
+    }
+
+    pub fn derive_path(&self, path: &Slip10Path) -> Result<Self, CryptoError> {
+        let mut key = Self { secret_key: self.secret_key, chain_code: self.chain_code };
+        for index in &path.indexes {
+            key = key.derive_child(*index)?;
+        }
+        Ok(key)
+    }
+
+    pub fn secret_key(&self) -> SigningSecretKey {
+        SigningSecretKey::from_bytes(self.secret_key)
+    }
+
+    pub fn public_key(&self) -> SigningPublicKey {
+        SigningKey::from_bytes(&self.secret_key).verifying_key().into()
+    }
+
+    pub fn chain_code(&self) -> &[u8; SLIP10_CHAIN_CODE_BYTES] {
+        &self.chain_code
+    }
+
+    fn from_hmac(key: &[u8], data: &[&[u8]]) -> Result<Self, CryptoError> {
+        let mut mac = Hmac::<Sha512>::new_from_slice(key)
+            .map_err(|e| CryptoError::KeyDerivationError(format!("HMAC-SHA512 setup failed: {}", e)))?;
+        for part in data {
+            mac.update(part);
+        }
+        let output = Zeroizing::new(<[u8; 64]>::from(mac.finalize().into_bytes()));
+        let mut extended_key = Self { secret_key: [0u8; 32], chain_code: [0u8; 32] };
+        extended_key.secret_key.copy_from_slice(&output[..32]);
+        extended_key.chain_code.copy_from_slice(&output[32..]);
+        Ok(extended_key)
+    }
+}
+
+/// Derives the SLIP-0010 Ed25519 keypair at `path` from a BIP-39 seed.
+///
+/// # Returns
+/// * `Ok((SigningSecretKey, SigningPublicKey))` for the key at `path`.
+/// * `Err(CryptoError::KeyDerivationError)` if the seed length is outside the BIP-32 limits.
+pub fn slip10_derive_keypair(
+    seed: &MasterSeed,
+    path: &Slip10Path,
+) -> Result<(SigningSecretKey, SigningPublicKey), CryptoError> {
+    let key = Slip10ExtendedKey::master(seed)?.derive_path(path)?;
+    Ok((key.secret_key(), key.public_key()))
+}
+
+// How an identity signing key is derived from the master seed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdentityScheme {
+    // The original HKDF hierarchy (m/identity/signing/<purpose>)
+    Hkdf { purpose: String },
+    // SLIP-0010, for keys that must match other Ed25519 wallets
+    Slip10 { path: Slip10Path },
+}
+
+/// Derives an identity signing keypair with the selected scheme.
+///
+/// # Returns
+/// * `Ok((SigningSecretKey, SigningPublicKey))` for the identity.
+/// * `Err(CryptoError::KeyDerivationError)` if derivation fails.
+pub fn derive_identity_keypair(
+    seed: &MasterSeed,
+    scheme: &IdentityScheme,
+) -> Result<(SigningSecretKey, SigningPublicKey), CryptoError> {
+    match scheme {
+        IdentityScheme::Hkdf { purpose } => {
+            derive_identity_signing_keypair(&derive_root_identity_secret(seed)?, purpose)
+        }
+        IdentityScheme::Slip10 { path } => slip10_derive_keypair(seed, path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // SLIP-0010 test vector 1 for ed25519: (path, chain code, private key, public key without
+    // the 0x00 prefix the spec prints)
+    const VECTOR_1_SEED: &str = "000102030405060708090a0b0c0d0e0f";
+    const VECTOR_1: &[(&str, &str, &str, &str)] = &[
+        (
+            "m",
+            "90046a93de5380a72b5e45010748567d5ea02bbf6522f979e05c0d8d8ca9fffb",
+            "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7",
+            "a4b2856bfec510abab89753fac1ac0e1112364e7d250545963f135f2a33188ed",
+        ),
+        (
+            "m/0H",
+            "8b59aa11380b624e81507a27fedda59fea6d0b779a778918a2fd3590e16e9c69",
+            "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3",
+            "8c8a13df77a28f3445213a0f432fde644acaa215fc72dcdf300d5efaa85d350c",
+        ),
+        (
+            "m/0H/1H",
+            "a320425f77d1b5c2505a6b1b27382b37368ee640e3557c315416801243552f14",
+            "b1d0bad404bf35da785a64ca1ac54b2617211d2777696fbffaf208f746ae84f2",
+            "1932a5270f335bed617d5b935c80aedb1a35bd9fc1e31acafd5372c30f5c1187",
+        ),
+        (
+            "m/0H/1H/2H",
+            "2e69929e00b5ab250f49c3fb1c12f252de4fed2c1db88387094a0f8c4c9ccd6c",
+            "92a5b23c0b8a99e37d07df3fb9966917f5d06e02ddbd909c7e184371463e9fc9",
+            "ae98736566d30ed0e9d2f4486a64bc95740d89c7db33f52121f8ea8f76ff0fc1",
+        ),
+        (
+            "m/0H/1H/2H/2H",
+            "8f6d87f93d750e0efccda017d662a1b31a266e4a6f5993b15f5c1f07f74dd5cc",
+            "30d1dc7e5fc04c31219ab25a27ae00b50f6fd66622f6e9c913253d6511d1e662",
+            "8abae2d66361c879b900d204ad2cc4984fa2aa344dd7ddc46007329ac76c429c",
+        ),
+        (
+            "m/0H/1H/2H/2H/1000000000H",
+            "68789923a0cac2cd5a29172a475fe9e0fb14cd6adb5ad98a3fa70333e7afa230",
+            "8f94d394a8e8fd6b1bc2f3f49f5c47e385281d5c17e65324b0f62483e37e8793",
+            "3c24da049451555d51a7014a37337aa4e12d41e485abccfa46b47dfb2af54b7a",
+        ),
+    ];
+
+    // SLIP-0010 test vector 2 for ed25519, same layout as vector 1
+    const VECTOR_2_SEED: &str = "fffcf9f6f3f0edeae7e4e1dedbd8d5d2cfccc9c6c3c0bdbab7b4b1aeaba8a5a29f9c999693908d8a8784817e7b7875726f6c696663605d5a5754514e4b484542";
+    const VECTOR_2: &[(&str, &str, &str, &str)] = &[
+        (
+            "m",
+            "ef70a74db9c3a5af931b5fe73ed8e1a53464133654fd55e7a66f8570b8e33c3b",
+            "171cb88b1b3c1db25add599712e36245d75bc65a1a5c9e18d76f9f2b1eab4012",
+            "8fe9693f8fa62a4305a140b9764c5ee01e455963744fe18204b4fb948249308a",
+        ),
+        (
+            "m/0H",
+            "0b78a3226f915c082bf118f83618a618ab6dec793752624cbeb622acb562862d",
+            "1559eb2bbec5790b0c65d8693e4d0875b1747f4970ae8b650486ed7470845635",
+            "86fab68dcb57aa196c77c5f264f215a112c22a912c10d123b0d03c3c28ef1037",
+        ),
+        (
+            "m/0H/2147483647H",
+            "138f0b2551bcafeca6ff2aa88ba8ed0ed8de070841f0c4ef0165df8181eaad7f",
+            "ea4f5bfe8694d8bb74b7b59404632fd5968b774ed545e810de9c32a4fb4192f4",
+            "5ba3b9ac6e90e83effcd25ac4e58a1365a9e35a3d3ae5eb07b9e4d90bcf7506d",
+        ),
+        (
+            "m/0H/2147483647H/1H",
+            "73bd9fff1cfbde33a1b846c27085f711c0fe2d66fd32e139d3ebc28e5a4a6b90",
+            "3757c7577170179c7868353ada796c839135b3d30554bbb74a4b1e4a5a58505c",
+            "2e66aa57069c86cc18249aecf5cb5a9cebbfd6fadeab056254763874a9352b45",
+        ),
+        (
+            "m/0H/2147483647H/1H/2147483646H",
+            "0902fe8a29f9140480a00ef244bd183e8a13288e4412d8389d140aac1794825a",
+            "5837736c89570de861ebc173b1086da4f505d4adb387c6a1b1342d5e4ac9ec72",
+            "e33c0f7d81d843c572275f287498e8d408654fdf0d1e065b84e2e6f157aab09b",
+        ),
+        (
+            "m/0H/2147483647H/1H/2147483646H/2H",
+            "5d70af781f3a37b829f0d060924d5e960bdc02e85423494afc0b1a41bbe196d4",
+            "551d333177df541ad876a60ea71f00447931c0a9da16f227c11ea080d7391b8d",
+            "47150c75db263559a70d5778bf36abbab30fb061ad69f69ece61a72b0cfa4fc0",
+        ),
+    ];
+
+    fn check_vector(seed_hex: &str, vector: &[(&str, &str, &str, &str)]) {
+        let seed = MasterSeed::from_bytes(hex::decode(seed_hex).unwrap());
+        let master = Slip10ExtendedKey::master(&seed).unwrap();
+        for (path, chain_code, private_key, public_key) in vector {
+            let key = master.derive_path(&path.parse().unwrap()).unwrap();
+            assert_eq!(hex::encode(key.chain_code()), *chain_code, "chain code at {}", path);
+            assert_eq!(hex::encode(key.secret_key().expose_secret()), *private_key, "private key at {}", path);
+            assert_eq!(hex::encode(key.public_key().as_bytes()), *public_key, "public key at {}", path);
+        }
+    }
+
+    #[test]
+    fn test_slip10_vector_1() {
+        check_vector(VECTOR_1_SEED, VECTOR_1);
+    }
+
+    #[test]
+    fn test_slip10_vector_2() {
+        check_vector(VECTOR_2_SEED, VECTOR_2);
+    }
+
+    #[test]
+    fn test_path_parsing() {
+        let path: Slip10Path = "m/44'/501h/0H".parse().unwrap();
+        assert_eq!(path.indexes(), &[44 + SLIP10_HARDENED_OFFSET, 501 + SLIP10_HARDENED_OFFSET, SLIP10_HARDENED_OFFSET]);
+        assert_eq!(path.to_string(), "m/44'/501'/0'");
+        assert_eq!("m".parse::<Slip10Path>().unwrap(), Slip10Path::master());
+
+        for text in ["", "44'", "m/44", "m/44'/0", "m/2147483648'", "m/'", "m/-1'", "m/+1'", "m//0'"] {
+            assert!(
+                matches!(text.parse::<Slip10Path>(), Err(CryptoError::InvalidDerivationPath(_))),
+                "{:?} should be rejected",
+                text
+            );
+        }
+        assert!(Slip10Path::from_indexes(vec![44]).is_err());
+        let master = Slip10ExtendedKey::master(&MasterSeed::from_bytes(vec![1u8; 64])).unwrap();
+        assert!(matches!(master.derive_child(0), Err(CryptoError::InvalidDerivationPath(_))));
+    }
+
+    #[test]
+    fn test_seed_length_limits() {
+        assert!(Slip10ExtendedKey::master(&MasterSeed::from_bytes(vec![0u8; 15])).is_err());
+        assert!(Slip10ExtendedKey::master(&MasterSeed::from_bytes(vec![0u8; 65])).is_err());
+        assert!(Slip10ExtendedKey::master(&MasterSeed::from_bytes(vec![0u8; 64])).is_ok());
+    }
+
+    #[test]
+    fn test_identity_schemes() {
+        let seed = MasterSeed::from_bytes(vec![7u8; 64]);
+        let hkdf = IdentityScheme::Hkdf { purpose: "primary-chain-signing".to_string() };
+        let slip10 = IdentityScheme::Slip10 { path: "m/44'/0'".parse().unwrap() };
+
+        let (_, hkdf_public) = derive_identity_keypair(&seed, &hkdf).unwrap();
+        let rik = derive_root_identity_secret(&seed).unwrap();
+        assert_eq!(hkdf_public, derive_identity_signing_keypair(&rik, "primary-chain-signing").unwrap().1);
+
+        let (_, slip10_public) = derive_identity_keypair(&seed, &slip10).unwrap();
+        assert_eq!(slip10_public, slip10_derive_keypair(&seed, &"m/44'/0'".parse().unwrap()).unwrap().1);
+        assert_ne!(hkdf_public, slip10_public);
+    }
+}
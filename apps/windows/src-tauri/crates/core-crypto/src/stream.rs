@@ -0,0 +1,312 @@
+// --- Streaming Symmetric Encryption (STREAM construction) ---
+//
+// Encrypts data of arbitrary length over `Read`/`Write` without holding it in memory, using the
+// STREAM segmented AEAD construction (Hoang, Reyhanitabar, Rogaway, Vizár) on top of
+// XChaCha20-Poly1305. Each chunk's nonce is built from a random per-stream prefix, a 32-bit
+// big-endian chunk counter and a last-chunk flag, so truncation, reordering, duplication and
+// swapping chunks between streams all cause decryption to fail.
+//
+// Wire format:
+//   nonce prefix (19 bytes) || chunk_0 || chunk_1 || ... || chunk_n
+// Every chunk except the last holds exactly `STREAM_CHUNK_BYTES` of plaintext plus a 16-byte tag.
+// The last chunk holds between 0 and `STREAM_CHUNK_BYTES` of plaintext plus its tag, so an empty
+// input still produces one (empty, authenticated) last chunk.
+
+use crate::{CryptoError, SymKey};
+use chacha20poly1305::aead::stream::{DecryptorBE32, EncryptorBE32};
+use chacha20poly1305::aead::{KeyInit, OsRng, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305};
+use rand::RngCore;
+use std::io::{self, Read, Write};
+
+// Plaintext bytes per chunk (64 KiB)
+pub const STREAM_CHUNK_BYTES: usize = 64 * 1024;
+// 24-byte XChaCha nonce minus the 4-byte counter and 1-byte last-chunk flag added by STREAM
+pub const STREAM_NONCE_PREFIX_BYTES: usize = 19;
+// Poly1305 tag appended to every chunk
+pub const STREAM_TAG_BYTES: usize = 16;
+
+const STREAM_CIPHERTEXT_CHUNK_BYTES: usize = STREAM_CHUNK_BYTES + STREAM_TAG_BYTES;
+
+// Reads until `buf` is full or the reader is exhausted, returning the number of bytes read.
+// Unlike `read_exact`, hitting EOF early is not an error: it is how the last chunk is detected.
+fn read_up_to<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// Encrypts everything readable from `reader` and writes the framed ciphertext to `writer`.
+///
+/// `associated_data`, if provided, is authenticated with every chunk and must be supplied again
+/// to `decrypt_stream`.
+///
+/// # Returns
+/// * `Ok(u64)` with the number of plaintext bytes encrypted.
+/// * `Err(CryptoError::IoError)` if reading or writing fails.
+pub fn encrypt_stream<R: Read, W: Write>(
+    key: &SymKey,
+    reader: &mut R,
+    writer: &mut W,
+    associated_data: Option<&[u8]>,
+) -> Result<u64, CryptoError> {
+    let aad = associated_data.unwrap_or_default();
+
+    let mut nonce_prefix = [0u8; STREAM_NONCE_PREFIX_BYTES];
+    OsRng.fill_bytes(&mut nonce_prefix);
+    writer.write_all(&nonce_prefix)?;
+
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(key.expose_secret()));
+    let mut encryptor = EncryptorBE32::from_aead(cipher, nonce_prefix.as_ref().into());
+
+    let mut current = vec![0u8; STREAM_CHUNK_BYTES];
+    let mut next = vec![0u8; STREAM_CHUNK_BYTES];
+    let mut current_len = read_up_to(reader, &mut current)?;
+    let mut total: u64 = 0;
+
+    // A full chunk is only known not to be the last one once more data has been read after it
+    loop {
+        let next_len = if current_len == STREAM_CHUNK_BYTES {
+            read_up_to(reader, &mut next)?
+        } else {
+            0
+        };
+        total += current_len as u64;
+
+        if next_len == 0 {
+            let ciphertext = encryptor
+                .encrypt_last(Payload { msg: &current[..current_len], aad })
+                .map_err(|e| CryptoError::EncryptionError(e.to_string()))?;
+            writer.write_all(&ciphertext)?;
+            break;
+        }
+
+        let ciphertext = encryptor
+            .encrypt_next(Payload { msg: &current[..current_len], aad })
+            .map_err(|e| CryptoError::EncryptionError(e.to_string()))?;
+        writer.write_all(&ciphertext)?;
+
+        std::mem::swap(&mut current, &mut next);
+        current_len = next_len;
+    }
+
+    writer.flush()?;
+    Ok(total)
+}
+
+/// Decrypts a stream produced by `encrypt_stream`, writing plaintext to `writer` chunk by chunk.
+///
+/// Plaintext is only written after its chunk has been authenticated, but a stream that fails
+/// part-way (e.g. because it was truncated) will already have written its earlier chunks.
+/// Callers must discard everything written to `writer` if this returns an error.
+///
+/// # Returns
+/// * `Ok(u64)` with the number of plaintext bytes recovered.
+/// * `Err(CryptoError::DecryptionError)` if the key or associated data is wrong, or the stream
+///   was tampered with, truncated, reordered or spliced.
+/// * `Err(CryptoError::IoError)` if reading or writing fails.
+pub fn decrypt_stream<R: Read, W: Write>(
+    key: &SymKey,
+    reader: &mut R,
+    writer: &mut W,
+    associated_data: Option<&[u8]>,
+) -> Result<u64, CryptoError> {
+    let aad = associated_data.unwrap_or_default();
+
+    let mut nonce_prefix = [0u8; STREAM_NONCE_PREFIX_BYTES];
+    if read_up_to(reader, &mut nonce_prefix)? != STREAM_NONCE_PREFIX_BYTES {
+        return Err(CryptoError::DecryptionError);
+    }
+
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(key.expose_secret()));
+    let mut decryptor = DecryptorBE32::from_aead(cipher, nonce_prefix.as_ref().into());
+
+    let mut current = vec![0u8; STREAM_CIPHERTEXT_CHUNK_BYTES];
+    let mut next = vec![0u8; STREAM_CIPHERTEXT_CHUNK_BYTES];
+    let mut current_len = read_up_to(reader, &mut current)?;
+    let mut total: u64 = 0;
+
+    loop {
+        let next_len = if current_len == STREAM_CIPHERTEXT_CHUNK_BYTES {
+            read_up_to(reader, &mut next)?
+        } else {
+            0
+        };
+
+        if next_len == 0 {
+            let plaintext = decryptor
+                .decrypt_last(Payload { msg: &current[..current_len], aad })
+                .map_err(|_| CryptoError::DecryptionError)?;
+            total += plaintext.len() as u64;
+            writer.write_all(&plaintext)?;
+            break;
+        }
+
+        let plaintext = decryptor
+            .decrypt_next(Payload { msg: &current[..current_len], aad })
+            .map_err(|_| CryptoError::DecryptionError)?;
+        total += plaintext.len() as u64;
+        writer.write_all(&plaintext)?;
+
+        std::mem::swap(&mut current, &mut next);
+        current_len = next_len;
+    }
+
+    writer.flush()?;
+    Ok(total)
+}
+
+// Size of the ciphertext `encrypt_stream` produces for `plaintext_len` bytes of input
+pub fn stream_ciphertext_len(plaintext_len: u64) -> u64 {
+    let chunk = STREAM_CHUNK_BYTES as u64;
+    // An exact multiple of the chunk size still ends with a full last chunk, and empty input
+    // still produces one empty last chunk.
+    let chunks = plaintext_len.div_ceil(chunk).max(1);
+    STREAM_NONCE_PREFIX_BYTES as u64 + plaintext_len + chunks * STREAM_TAG_BYTES as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::generate_random_key;
+    use std::io::Cursor;
+
+    fn random_bytes(len: usize) -> Vec<u8> {
+        let mut data = vec![0u8; len];
+        OsRng.fill_bytes(&mut data);
+        data
+    }
+
+    fn encrypt_to_vec(key: &SymKey, plaintext: &[u8], aad: Option<&[u8]>) -> Vec<u8> {
+        let mut ciphertext = Vec::new();
+        let written = encrypt_stream(key, &mut Cursor::new(plaintext), &mut ciphertext, aad)
+            .expect("Stream encryption failed");
+        assert_eq!(written, plaintext.len() as u64);
+        ciphertext
+    }
+
+    fn decrypt_to_vec(key: &SymKey, ciphertext: &[u8], aad: Option<&[u8]>) -> Result<Vec<u8>, CryptoError> {
+        let mut plaintext = Vec::new();
+        decrypt_stream(key, &mut Cursor::new(ciphertext), &mut plaintext, aad)?;
+        Ok(plaintext)
+    }
+
+    #[test]
+    fn test_stream_roundtrip_various_sizes() {
+        let key = generate_random_key();
+        let sizes = [
+            0,
+            1,
+            STREAM_CHUNK_BYTES - 1,
+            STREAM_CHUNK_BYTES,
+            STREAM_CHUNK_BYTES + 1,
+            3 * STREAM_CHUNK_BYTES,
+            3 * STREAM_CHUNK_BYTES + 12345,
+        ];
+
+        for size in sizes {
+            let plaintext = random_bytes(size);
+            let ciphertext = encrypt_to_vec(&key, &plaintext, Some(b"file-id"));
+            assert_eq!(ciphertext.len() as u64, stream_ciphertext_len(size as u64), "size {}", size);
+
+            let decrypted = decrypt_to_vec(&key, &ciphertext, Some(b"file-id")).expect("Stream decryption failed");
+            assert_eq!(decrypted, plaintext, "roundtrip failed for size {}", size);
+        }
+    }
+
+    #[test]
+    fn test_stream_wrong_key_or_aad() {
+        let key = generate_random_key();
+        let plaintext = random_bytes(STREAM_CHUNK_BYTES + 10);
+        let ciphertext = encrypt_to_vec(&key, &plaintext, Some(b"file-id"));
+
+        let result = decrypt_to_vec(&generate_random_key(), &ciphertext, Some(b"file-id"));
+        assert_eq!(result.unwrap_err(), CryptoError::DecryptionError);
+
+        let result = decrypt_to_vec(&key, &ciphertext, Some(b"other-id"));
+        assert_eq!(result.unwrap_err(), CryptoError::DecryptionError);
+    }
+
+    #[test]
+    fn test_stream_detects_truncation() {
+        let key = generate_random_key();
+        let plaintext = random_bytes(3 * STREAM_CHUNK_BYTES);
+        let ciphertext = encrypt_to_vec(&key, &plaintext, None);
+
+        // Dropping the whole last chunk leaves a stream ending on a non-final chunk boundary
+        let truncated = &ciphertext[..ciphertext.len() - STREAM_CIPHERTEXT_CHUNK_BYTES];
+        assert_eq!(decrypt_to_vec(&key, truncated, None).unwrap_err(), CryptoError::DecryptionError);
+
+        // Cutting into the last chunk
+        let truncated = &ciphertext[..ciphertext.len() - 1];
+        assert_eq!(decrypt_to_vec(&key, truncated, None).unwrap_err(), CryptoError::DecryptionError);
+
+        // Only the header
+        let truncated = &ciphertext[..STREAM_NONCE_PREFIX_BYTES];
+        assert_eq!(decrypt_to_vec(&key, truncated, None).unwrap_err(), CryptoError::DecryptionError);
+
+        // Not even a full header
+        let truncated = &ciphertext[..STREAM_NONCE_PREFIX_BYTES - 1];
+        assert_eq!(decrypt_to_vec(&key, truncated, None).unwrap_err(), CryptoError::DecryptionError);
+    }
+
+    #[test]
+    fn test_stream_detects_reordering() {
+        let key = generate_random_key();
+        let plaintext = random_bytes(3 * STREAM_CHUNK_BYTES + 100);
+        let ciphertext = encrypt_to_vec(&key, &plaintext, None);
+
+        // Swap the first two chunks
+        let first = STREAM_NONCE_PREFIX_BYTES..STREAM_NONCE_PREFIX_BYTES + STREAM_CIPHERTEXT_CHUNK_BYTES;
+        let second = first.end..first.end + STREAM_CIPHERTEXT_CHUNK_BYTES;
+        let mut reordered = ciphertext[..first.start].to_vec();
+        reordered.extend_from_slice(&ciphertext[second.clone()]);
+        reordered.extend_from_slice(&ciphertext[first]);
+        reordered.extend_from_slice(&ciphertext[second.end..]);
+
+        assert_eq!(decrypt_to_vec(&key, &reordered, None).unwrap_err(), CryptoError::DecryptionError);
+    }
+
+    #[test]
+    fn test_stream_detects_chunk_swap_between_streams() {
+        let key = generate_random_key();
+        let plaintext_a = random_bytes(2 * STREAM_CHUNK_BYTES + 1);
+        let plaintext_b = random_bytes(2 * STREAM_CHUNK_BYTES + 1);
+        let ciphertext_a = encrypt_to_vec(&key, &plaintext_a, None);
+        let ciphertext_b = encrypt_to_vec(&key, &plaintext_b, None);
+
+        // Replace stream A's first chunk with stream B's first chunk (same key, same position)
+        let first = STREAM_NONCE_PREFIX_BYTES..STREAM_NONCE_PREFIX_BYTES + STREAM_CIPHERTEXT_CHUNK_BYTES;
+        let mut spliced = ciphertext_a.clone();
+        spliced[first.clone()].copy_from_slice(&ciphertext_b[first]);
+
+        assert_eq!(decrypt_to_vec(&key, &spliced, None).unwrap_err(), CryptoError::DecryptionError);
+    }
+
+    #[test]
+    fn test_stream_detects_appended_data() {
+        let key = generate_random_key();
+        let plaintext = random_bytes(100);
+        let mut ciphertext = encrypt_to_vec(&key, &plaintext, None);
+        ciphertext.extend_from_slice(&[0u8; 32]);
+
+        assert_eq!(decrypt_to_vec(&key, &ciphertext, None).unwrap_err(), CryptoError::DecryptionError);
+    }
+
+    #[test]
+    fn test_stream_tampered_chunk() {
+        let key = generate_random_key();
+        let plaintext = random_bytes(2 * STREAM_CHUNK_BYTES);
+        let mut ciphertext = encrypt_to_vec(&key, &plaintext, None);
+        ciphertext[STREAM_NONCE_PREFIX_BYTES + 5] ^= 0x01;
+
+        assert_eq!(decrypt_to_vec(&key, &ciphertext, None).unwrap_err(), CryptoError::DecryptionError);
+    }
+}
@@ -0,0 +1,544 @@
+// --- Transactable Key Tokens ---
+//
+// A key token grants a holder rights over one piece of content. The root token is issued by the
+// content's token signing key (`derive_token_signing_keypair`), so anyone holding the matching
+// public key can verify it offline. A holder with the DELEGATE right can attenuate their token
+// into a sub-token for someone else, with the same or fewer rights and the same or an earlier
+// expiry; the sub-token is signed by the delegating holder and carries the whole chain back to the
+// root, so verification still only needs the issuer's public key.
+//
+// Every link is signed with the TransactableKeyToken signing context and verified strictly (see
+// `VerificationMode`). Link i > 0 also signs the signature of link i - 1, so a sub-token can't be
+// moved onto a different parent.
+//
+// Claims encoding:
+//   content id length (2, BE) || content id || holder key (32) || rights (4, BE) ||
+//   expires_at (8, BE, unix seconds) || nonce (16)
+// Token encoding:
+//   "PNKT" || version (1) || link count (1) || links
+//   link = claims length (2, BE) || claims || signature (64)
+
+use crate::{
+    derive_token_signing_keypair, sign_with_context, verify_with_context, ContentMasterKey,
+    CryptoError, Signature, SigningContext, SigningPublicKey, SigningSecretKey, SIGNATURE_BYTES,
+    SIGNING_PUBLIC_KEY_BYTES,
+};
+use ed25519_dalek::SigningKey;
+use rand::RngCore;
+use rand::rngs::OsRng;
+use std::ops::BitOr;
+
+pub const KEY_TOKEN_MAGIC: [u8; 4] = *b"PNKT";
+pub const KEY_TOKEN_VERSION: u8 = 1;
+pub const KEY_TOKEN_NONCE_BYTES: usize = 16;
+// Longest delegation chain accepted, including the root
+pub const KEY_TOKEN_MAX_CHAIN_LENGTH: usize = 8;
+pub const KEY_TOKEN_MAX_CONTENT_ID_BYTES: usize = 1024;
+
+const CLAIMS_FIXED_BYTES: usize = 2 + SIGNING_PUBLIC_KEY_BYTES + 4 + 8 + KEY_TOKEN_NONCE_BYTES;
+
+// Set of rights a token grants. Unknown bits are rejected when parsing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TokenRights(u32);
+
+impl TokenRights {
+    // Decrypt and view the content
+    pub const READ: TokenRights = TokenRights(1 << 0);
+    // Keep an offline copy of the content key
+    pub const DOWNLOAD: TokenRights = TokenRights(1 << 1);
+    // Sell or hand the token on as a whole
+    pub const TRANSFER: TokenRights = TokenRights(1 << 2);
+    // Attenuate the token into sub-tokens
+    pub const DELEGATE: TokenRights = TokenRights(1 << 3);
+
+    const ALL_BITS: u32 = 0b1111;
+
+    pub const fn empty() -> Self {
+        TokenRights(0)
+    }
+
+    pub const fn bits(&self) -> u32 {
+        self.0
+    }
+
+    // Returns None if any bit doesn't correspond to a known right
+    pub const fn from_bits(bits: u32) -> Option<Self> {
+        if bits & !Self::ALL_BITS == 0 { Some(TokenRights(bits)) } else { None }
+    }
+
+    pub const fn contains(&self, other: TokenRights) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for TokenRights {
+    type Output = TokenRights;
+
+    fn bitor(self, rhs: TokenRights) -> TokenRights {
+        TokenRights(self.0 | rhs.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenClaims {
+    pub content_id: Vec<u8>,
+    pub holder: SigningPublicKey,
+    pub rights: TokenRights,
+    // Unix time (seconds) from which the token is no longer valid
+    pub expires_at: u64,
+    pub nonce: [u8; KEY_TOKEN_NONCE_BYTES],
+}
+
+impl TokenClaims {
+    // Claims with a fresh random nonce
+    pub fn new(content_id: &[u8], holder: SigningPublicKey, rights: TokenRights, expires_at: u64) -> Self {
+        let mut nonce = [0u8; KEY_TOKEN_NONCE_BYTES];
+        OsRng.fill_bytes(&mut nonce);
+        Self { content_id: content_id.to_vec(), holder, rights, expires_at, nonce }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(CLAIMS_FIXED_BYTES + self.content_id.len());
+        bytes.extend_from_slice(&(self.content_id.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(&self.content_id);
+        bytes.extend_from_slice(self.holder.as_bytes());
+        bytes.extend_from_slice(&self.rights.bits().to_be_bytes());
+        bytes.extend_from_slice(&self.expires_at.to_be_bytes());
+        bytes.extend_from_slice(&self.nonce);
+        bytes
+    }
+
+    /// Parses claims produced by `to_bytes`.
+    ///
+    /// # Returns
+    /// * `Err(CryptoError::MalformedToken)` if the encoding is invalid or has trailing bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CryptoError> {
+        if bytes.len() < CLAIMS_FIXED_BYTES {
+            return Err(malformed("claims are truncated"));
+        }
+        let id_len = u16::from_be_bytes([bytes[0], bytes[1]]) as usize;
+        if id_len > KEY_TOKEN_MAX_CONTENT_ID_BYTES {
+            return Err(malformed("content id is too long"));
+        }
+        if bytes.len() != CLAIMS_FIXED_BYTES + id_len {
+            return Err(malformed("claims length does not match content id length"));
+        }
+        let (content_id, rest) = bytes[2..].split_at(id_len);
+        let (holder, rest) = rest.split_at(SIGNING_PUBLIC_KEY_BYTES);
+        let (rights, rest) = rest.split_at(4);
+        let (expires_at, nonce) = rest.split_at(8);
+
+        let holder = SigningPublicKey::try_from_bytes_strict(holder.try_into().expect("slice length is fixed"))
+            .map_err(|_| malformed("holder key is not a valid, non-weak public key"))?;
+        let rights = TokenRights::from_bits(u32::from_be_bytes(rights.try_into().expect("slice length is fixed")))
+            .ok_or_else(|| malformed("unknown rights bits"))?;
+        Ok(Self {
+            content_id: content_id.to_vec(),
+            holder,
+            rights,
+            expires_at: u64::from_be_bytes(expires_at.try_into().expect("slice length is fixed")),
+            nonce: nonce.try_into().expect("slice length is fixed"),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TokenLink {
+    claims: TokenClaims,
+    signature: Signature,
+}
+
+// A root token plus any attenuations, ending at the current holder's claims
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyToken {
+    links: Vec<TokenLink>,
+}
+
+impl KeyToken {
+    // The current holder's claims (the last link)
+    pub fn claims(&self) -> &TokenClaims {
+        &self.links.last().expect("tokens always have a root link").claims
+    }
+
+    // The claims the content owner originally issued
+    pub fn root_claims(&self) -> &TokenClaims {
+        &self.links[0].claims
+    }
+
+    // 1 for a root token, +1 for each attenuation
+    pub fn chain_len(&self) -> usize {
+        self.links.len()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&KEY_TOKEN_MAGIC);
+        bytes.push(KEY_TOKEN_VERSION);
+        bytes.push(self.links.len() as u8);
+        for link in &self.links {
+            let claims = link.claims.to_bytes();
+            bytes.extend_from_slice(&(claims.len() as u16).to_be_bytes());
+            bytes.extend_from_slice(&claims);
+            bytes.extend_from_slice(link.signature.as_bytes());
+        }
+        bytes
+    }
+
+    /// Parses a token produced by `to_bytes`. This only checks the encoding; use `verify_token`
+    /// before trusting the claims.
+    ///
+    /// # Returns
+    /// * `Err(CryptoError::MalformedToken)` if the encoding is invalid.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CryptoError> {
+        if bytes.len() < 6 || bytes[..4] != KEY_TOKEN_MAGIC {
+            return Err(malformed("not a key token"));
+        }
+        if bytes[4] != KEY_TOKEN_VERSION {
+            return Err(CryptoError::MalformedToken(format!("unsupported version {}", bytes[4])));
+        }
+        let count = bytes[5] as usize;
+        if count == 0 || count > KEY_TOKEN_MAX_CHAIN_LENGTH {
+            return Err(malformed("invalid chain length"));
+        }
+
+        let mut rest = &bytes[6..];
+        let mut links = Vec::with_capacity(count);
+        for _ in 0..count {
+            if rest.len() < 2 {
+                return Err(malformed("token is truncated"));
+            }
+            let claims_len = u16::from_be_bytes([rest[0], rest[1]]) as usize;
+            if rest.len() < 2 + claims_len + SIGNATURE_BYTES {
+                return Err(malformed("token is truncated"));
+            }
+            let claims = TokenClaims::from_bytes(&rest[2..2 + claims_len])?;
+            let signature_bytes: [u8; SIGNATURE_BYTES] = rest[2 + claims_len..2 + claims_len + SIGNATURE_BYTES]
+                .try_into()
+                .expect("slice length is fixed");
+            let signature = Signature::try_from_bytes(&signature_bytes)
+                .map_err(|_| malformed("invalid signature encoding"))?;
+            links.push(TokenLink { claims, signature });
+            rest = &rest[2 + claims_len + SIGNATURE_BYTES..];
+        }
+        if !rest.is_empty() {
+            return Err(malformed("trailing bytes after token"));
+        }
+        Ok(Self { links })
+    }
+}
+
+/// Issues a root token for the content `cmk` belongs to, signed with its token signing key.
+///
+/// # Returns
+/// * `Err(CryptoError::MalformedToken)` if the content id is longer than
+///   `KEY_TOKEN_MAX_CONTENT_ID_BYTES`.
+/// * `Err(CryptoError::WeakPublicKey)` if the holder key is small-order.
+pub fn issue_token(cmk: &ContentMasterKey, claims: TokenClaims) -> Result<KeyToken, CryptoError> {
+    if claims.content_id.len() > KEY_TOKEN_MAX_CONTENT_ID_BYTES {
+        return Err(malformed("content id is too long"));
+    }
+    if claims.holder.is_weak() {
+        return Err(CryptoError::WeakPublicKey);
+    }
+    let (token_secret_key, _) = derive_token_signing_keypair(cmk)?;
+    let signature = sign_with_context(&token_secret_key, SigningContext::TransactableKeyToken, &link_message(&claims, None))?;
+    Ok(KeyToken { links: vec![TokenLink { claims, signature }] })
+}
+
+/// Verifies a token offline against the content's token public key (the public half of
+/// `derive_token_signing_keypair`), at unix time `now`.
+///
+/// # Returns
+/// * `Ok(&TokenClaims)` with the current holder's (effective) claims.
+/// * `Err(CryptoError::ForgedToken)` if any signature is invalid, any holder key is weak, or an
+///   attenuation widens the rights, extends the expiry, changes the content or wasn't allowed to
+///   delegate.
+/// * `Err(CryptoError::TokenExpired)` if the token is genuine but has expired.
+pub fn verify_token<'a>(
+    token: &'a KeyToken,
+    issuer_public_key: &SigningPublicKey,
+    now: u64,
+) -> Result<&'a TokenClaims, CryptoError> {
+    let mut parent: Option<&TokenLink> = None;
+    for (index, link) in token.links.iter().enumerate() {
+        if link.claims.holder.is_weak() {
+            return Err(CryptoError::ForgedToken(format!("link {}: holder key is weak", index)));
+        }
+        let signer = match parent {
+            None => issuer_public_key,
+            Some(parent) => {
+                check_attenuation(&parent.claims, &link.claims)
+                    .map_err(|reason| CryptoError::ForgedToken(format!("link {}: {}", index, reason)))?;
+                &parent.claims.holder
+            }
+        };
+        let message = link_message(&link.claims, parent.map(|p| &p.signature));
+        verify_with_context(signer, SigningContext::TransactableKeyToken, &message, &link.signature)
+            .map_err(|_| CryptoError::ForgedToken(format!("link {}: invalid signature", index)))?;
+        parent = Some(link);
+    }
+
+    // Attenuation never extends the expiry, so the last link expires first
+    let claims = token.claims();
+    if now >= claims.expires_at {
+        return Err(CryptoError::TokenExpired { expires_at: claims.expires_at });
+    }
+    Ok(claims)
+}
+
+/// Derives a narrower sub-token for `new_holder`, signed by the current holder.
+///
+/// # Returns
+/// * `Err(CryptoError::InvalidTokenAttenuation)` if `holder_secret_key` isn't the current
+///   holder's key, the token lacks the DELEGATE right, the new rights or expiry exceed the
+///   current ones, or the chain would exceed `KEY_TOKEN_MAX_CHAIN_LENGTH`.
+/// * `Err(CryptoError::WeakPublicKey)` if `new_holder` is small-order.
+pub fn attenuate_token(
+    token: &KeyToken,
+    holder_secret_key: &SigningSecretKey,
+    new_holder: SigningPublicKey,
+    rights: TokenRights,
+    expires_at: u64,
+) -> Result<KeyToken, CryptoError> {
+    let parent = token.links.last().expect("tokens always have a root link");
+    let holder_public = SigningKey::from_bytes(holder_secret_key.expose_secret()).verifying_key();
+    if holder_public.as_bytes() != parent.claims.holder.as_bytes() {
+        return Err(CryptoError::InvalidTokenAttenuation("signing key is not the token holder's".to_string()));
+    }
+    if token.links.len() >= KEY_TOKEN_MAX_CHAIN_LENGTH {
+        return Err(CryptoError::InvalidTokenAttenuation("delegation chain is too long".to_string()));
+    }
+    if new_holder.is_weak() {
+        return Err(CryptoError::WeakPublicKey);
+    }
+
+    let claims = TokenClaims::new(&parent.claims.content_id, new_holder, rights, expires_at);
+    check_attenuation(&parent.claims, &claims).map_err(|reason| CryptoError::InvalidTokenAttenuation(reason.to_string()))?;
+
+    let signature = sign_with_context(
+        holder_secret_key,
+        SigningContext::TransactableKeyToken,
+        &link_message(&claims, Some(&parent.signature)),
+    )?;
+    let mut links = token.links.clone();
+    links.push(TokenLink { claims, signature });
+    Ok(KeyToken { links })
+}
+
+// Rules every sub-token must follow relative to its parent
+fn check_attenuation(parent: &TokenClaims, child: &TokenClaims) -> Result<(), &'static str> {
+    if !parent.rights.contains(TokenRights::DELEGATE) {
+        return Err("parent token does not allow delegation");
+    }
+    if !parent.rights.contains(child.rights) {
+        return Err("rights exceed the parent token's");
+    }
+    if child.expires_at > parent.expires_at {
+        return Err("expiry is later than the parent token's");
+    }
+    if child.content_id != parent.content_id {
+        return Err("content id differs from the parent token's");
+    }
+    Ok(())
+}
+
+fn link_message(claims: &TokenClaims, parent_signature: Option<&Signature>) -> Vec<u8> {
+    let mut message = claims.to_bytes();
+    if let Some(parent_signature) = parent_signature {
+        message.extend_from_slice(parent_signature.as_bytes());
+    }
+    message
+}
+
+fn malformed(reason: &str) -> CryptoError {
+    CryptoError::MalformedToken(reason.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{derive_content_master_key, derive_root_identity_secret, generate_signing_keypair, MasterSeed};
+
+    const NOW: u64 = 1_700_000_000;
+    const DAY: u64 = 86_400;
+
+    fn content_key() -> (ContentMasterKey, SigningPublicKey) {
+        let rik = derive_root_identity_secret(&MasterSeed::from_bytes(vec![7u8; 64])).unwrap();
+        let cmk = derive_content_master_key(&rik, b"content-42").unwrap();
+        let (_, issuer_public_key) = derive_token_signing_keypair(&cmk).unwrap();
+        (cmk, issuer_public_key)
+    }
+
+    fn root_token(holder: &SigningPublicKey, rights: TokenRights) -> (KeyToken, SigningPublicKey) {
+        let (cmk, issuer) = content_key();
+        let claims = TokenClaims::new(b"content-42", holder.clone(), rights, NOW + 30 * DAY);
+        (issue_token(&cmk, claims).unwrap(), issuer)
+    }
+
+    #[test]
+    fn test_issue_and_verify() {
+        let (_, holder) = generate_signing_keypair();
+        let (token, issuer) = root_token(&holder, TokenRights::READ | TokenRights::DOWNLOAD);
+
+        let claims = verify_token(&token, &issuer, NOW).unwrap();
+        assert_eq!(claims.content_id, b"content-42");
+        assert_eq!(claims.holder, holder);
+        assert!(claims.rights.contains(TokenRights::READ));
+        assert!(!claims.rights.contains(TokenRights::DELEGATE));
+
+        let parsed = KeyToken::from_bytes(&token.to_bytes()).unwrap();
+        assert_eq!(parsed, token);
+        assert!(verify_token(&parsed, &issuer, NOW).is_ok());
+    }
+
+    #[test]
+    fn test_expired_token() {
+        let (_, holder) = generate_signing_keypair();
+        let (token, issuer) = root_token(&holder, TokenRights::READ);
+        assert_eq!(
+            verify_token(&token, &issuer, NOW + 30 * DAY).unwrap_err(),
+            CryptoError::TokenExpired { expires_at: NOW + 30 * DAY }
+        );
+    }
+
+    #[test]
+    fn test_forged_tokens() {
+        let (_, holder) = generate_signing_keypair();
+        let (token, issuer) = root_token(&holder, TokenRights::READ);
+
+        // Wrong issuer (e.g. another content's token key)
+        let (_, other_issuer) = generate_signing_keypair();
+        assert!(matches!(verify_token(&token, &other_issuer, NOW), Err(CryptoError::ForgedToken(_))));
+
+        // Self-escalated rights
+        let mut escalated = token.clone();
+        escalated.links[0].claims.rights = TokenRights::READ | TokenRights::DELEGATE;
+        assert!(matches!(verify_token(&escalated, &issuer, NOW), Err(CryptoError::ForgedToken(_))));
+
+        // Extended expiry, via the wire format
+        let mut bytes = token.to_bytes();
+        let expiry_offset = 6 + 2 + 2 + b"content-42".len() + SIGNING_PUBLIC_KEY_BYTES + 4;
+        bytes[expiry_offset] ^= 0x01;
+        let tampered = KeyToken::from_bytes(&bytes).unwrap();
+        assert!(matches!(verify_token(&tampered, &issuer, NOW), Err(CryptoError::ForgedToken(_))));
+
+        // A raw signature over the claims is not a token signature
+        let (cmk, _) = content_key();
+        let (token_sk, _) = derive_token_signing_keypair(&cmk).unwrap();
+        let mut raw = token.clone();
+        raw.links[0].signature = crate::sign(&token_sk, &token.links[0].claims.to_bytes()).unwrap();
+        assert!(matches!(verify_token(&raw, &issuer, NOW), Err(CryptoError::ForgedToken(_))));
+    }
+
+    #[test]
+    fn test_malformed_tokens() {
+        let (_, holder) = generate_signing_keypair();
+        let (token, _) = root_token(&holder, TokenRights::READ);
+        let bytes = token.to_bytes();
+
+        assert!(matches!(KeyToken::from_bytes(&bytes[..bytes.len() - 1]), Err(CryptoError::MalformedToken(_))));
+        assert!(matches!(KeyToken::from_bytes(b"PNKX\x01\x01"), Err(CryptoError::MalformedToken(_))));
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(matches!(KeyToken::from_bytes(&trailing), Err(CryptoError::MalformedToken(_))));
+
+        let mut unknown_rights = bytes.clone();
+        let rights_offset = 6 + 2 + 2 + b"content-42".len() + SIGNING_PUBLIC_KEY_BYTES;
+        unknown_rights[rights_offset] = 0x80;
+        assert!(matches!(KeyToken::from_bytes(&unknown_rights), Err(CryptoError::MalformedToken(_))));
+
+        let mut no_links = bytes.clone();
+        no_links[5] = 0;
+        assert!(matches!(KeyToken::from_bytes(&no_links), Err(CryptoError::MalformedToken(_))));
+    }
+
+    #[test]
+    fn test_attenuation_chain() {
+        let (alice_sk, alice) = generate_signing_keypair();
+        let (bob_sk, bob) = generate_signing_keypair();
+        let (_, carol) = generate_signing_keypair();
+        let all = TokenRights::READ | TokenRights::DOWNLOAD | TokenRights::DELEGATE;
+        let (token, issuer) = root_token(&alice, all);
+
+        let for_bob = attenuate_token(&token, &alice_sk, bob.clone(), TokenRights::READ | TokenRights::DELEGATE, NOW + 7 * DAY).unwrap();
+        let for_carol = attenuate_token(&for_bob, &bob_sk, carol.clone(), TokenRights::READ, NOW + DAY).unwrap();
+        assert_eq!(for_carol.chain_len(), 3);
+        assert_eq!(for_carol.root_claims().holder, alice);
+
+        let parsed = KeyToken::from_bytes(&for_carol.to_bytes()).unwrap();
+        let claims = verify_token(&parsed, &issuer, NOW).unwrap();
+        assert_eq!(claims.holder, carol);
+        assert_eq!(claims.rights, TokenRights::READ);
+
+        // The sub-token expires on its own schedule
+        assert!(verify_token(&for_bob, &issuer, NOW + 2 * DAY).is_ok());
+        assert!(matches!(verify_token(&for_carol, &issuer, NOW + 2 * DAY), Err(CryptoError::TokenExpired { .. })));
+    }
+
+    #[test]
+    fn test_attenuation_rules() {
+        let (alice_sk, alice) = generate_signing_keypair();
+        let (bob_sk, bob) = generate_signing_keypair();
+        let (token, issuer) = root_token(&alice, TokenRights::READ | TokenRights::DELEGATE);
+
+        let widened = attenuate_token(&token, &alice_sk, bob.clone(), TokenRights::READ | TokenRights::DOWNLOAD, NOW + DAY);
+        assert!(matches!(widened, Err(CryptoError::InvalidTokenAttenuation(_))));
+        let extended = attenuate_token(&token, &alice_sk, bob.clone(), TokenRights::READ, NOW + 60 * DAY);
+        assert!(matches!(extended, Err(CryptoError::InvalidTokenAttenuation(_))));
+        let not_holder = attenuate_token(&token, &bob_sk, bob.clone(), TokenRights::READ, NOW + DAY);
+        assert!(matches!(not_holder, Err(CryptoError::InvalidTokenAttenuation(_))));
+
+        // Without DELEGATE the chain stops
+        let for_bob = attenuate_token(&token, &alice_sk, bob.clone(), TokenRights::READ, NOW + DAY).unwrap();
+        let (_, carol) = generate_signing_keypair();
+        assert!(matches!(
+            attenuate_token(&for_bob, &bob_sk, carol, TokenRights::READ, NOW + DAY),
+            Err(CryptoError::InvalidTokenAttenuation(_))
+        ));
+
+        // A hand-built widening sub-token (signed by the rightful holder) is rejected as forged
+        let mut forged = for_bob.clone();
+        forged.links[1].claims.rights = TokenRights::READ | TokenRights::DOWNLOAD;
+        forged.links[1].signature = sign_with_context(
+            &alice_sk,
+            SigningContext::TransactableKeyToken,
+            &link_message(&forged.links[1].claims, Some(&forged.links[0].signature)),
+        )
+        .unwrap();
+        assert!(matches!(verify_token(&forged, &issuer, NOW), Err(CryptoError::ForgedToken(_))));
+
+        // Grafting a sub-token onto a different root breaks the parent binding
+        let (other_root, _) = root_token(&alice, TokenRights::READ | TokenRights::DELEGATE);
+        let mut grafted = for_bob.clone();
+        grafted.links[0] = other_root.links[0].clone();
+        assert!(matches!(verify_token(&grafted, &issuer, NOW), Err(CryptoError::ForgedToken(_))));
+    }
+
+    #[test]
+    fn test_weak_holder_keys_rejected() {
+        // The identity point is small-order
+        let mut identity = [0u8; SIGNING_PUBLIC_KEY_BYTES];
+        identity[0] = 1;
+        let weak = SigningPublicKey::try_from_bytes(&identity).unwrap();
+        assert!(weak.is_weak());
+
+        let (cmk, issuer) = content_key();
+        let claims = TokenClaims::new(b"content-42", weak.clone(), TokenRights::READ, NOW + DAY);
+        assert_eq!(issue_token(&cmk, claims.clone()).unwrap_err(), CryptoError::WeakPublicKey);
+
+        let (alice_sk, alice) = generate_signing_keypair();
+        let (token, _) = root_token(&alice, TokenRights::READ | TokenRights::DELEGATE);
+        assert_eq!(
+            attenuate_token(&token, &alice_sk, weak, TokenRights::READ, NOW + DAY).unwrap_err(),
+            CryptoError::WeakPublicKey
+        );
+
+        // A hand-built token for a weak holder doesn't verify either
+        let (token_sk, _) = derive_token_signing_keypair(&cmk).unwrap();
+        let signature =
+            sign_with_context(&token_sk, SigningContext::TransactableKeyToken, &link_message(&claims, None)).unwrap();
+        let forged = KeyToken { links: vec![TokenLink { claims, signature }] };
+        assert!(matches!(verify_token(&forged, &issuer, NOW), Err(CryptoError::ForgedToken(_))));
+    }
+}
@@ -51,6 +51,37 @@ pub trait SecureStorage: Send + Sync {
     /// Retrieves the mnemonic phrase securely.
     fn retrieve_mnemonic(&self) -> Result<Option<String>, StorageError>;
 
+    /// Records whether the stored seed was derived with a BIP-39 passphrase.
+    /// The passphrase itself must never be stored: it is the user's to remember, and without it
+    /// the mnemonic alone restores a different wallet.
+    ///
+    /// The default implementation is for backends that predate passphrase support: it accepts
+    /// `false` (which `retrieve_passphrase_flag`'s default reports anyway) and refuses `true`, so a
+    /// passphrase-protected seed is never stored without its flag.
+    fn store_passphrase_flag(&self, uses_passphrase: bool) -> Result<(), StorageError> {
+        if uses_passphrase {
+            Err(StorageError::InternalError(
+                "this storage backend can't record passphrase-protected seeds".to_string(),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Retrieves whether the stored seed was derived with a BIP-39 passphrase.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(true)` if the seed needs a passphrase in addition to the mnemonic.
+    /// * `Ok(false)` if it doesn't, or if no flag was stored (seeds stored before passphrase
+    ///   support never used one).
+    /// * `Err(StorageError)` if an error occurred during retrieval.
+    ///
+    /// The default implementation always returns `Ok(false)`.
+    fn retrieve_passphrase_flag(&self) -> Result<bool, StorageError> {
+        Ok(false)
+    }
+
     // Optional: Consider adding a method to clear the seed if needed.
     // fn clear_seed(&self) -> Result<(), StorageError>;
 }
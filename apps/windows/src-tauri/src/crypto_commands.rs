@@ -2,7 +2,11 @@
 
 use core_crypto::*;
 use hex;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, BufWriter, Write};
 use tauri::command;
+use zeroize::Zeroizing;
 // No longer need Deref here
 // use std::ops::Deref;
 // Ed25519 types are only needed inside core-crypto
@@ -12,65 +16,152 @@ fn map_crypto_err(err: CryptoError) -> String {
     format!("Crypto Error: {}", err)
 }
 
+// Maps the optional `strict` flag of the verify commands to a VerificationMode (permissive unless
+// strict is requested)
+fn verification_mode(strict: Option<bool>) -> VerificationMode {
+    if strict.unwrap_or(false) {
+        VerificationMode::Strict
+    } else {
+        VerificationMode::Permissive
+    }
+}
+
 #[command]
 pub fn generate_signing_keypair_hex() -> Result<(String, String), String> {
     let (secret_key, public_key) = generate_signing_keypair();
     // Note: We return the secret key bytes (seed) as hex.
     // The frontend should handle this sensitive data appropriately.
     Ok((
-        hex::encode(secret_key.as_bytes()), // Use as_bytes()
+        hex::encode(secret_key.expose_secret()),
         hex::encode(public_key.as_bytes()), // Use as_bytes()
     ))
 }
 
+// Raw signing; messages starting with the signing-context domain are refused (use
+// sign_with_context_hex for those)
 #[command]
 pub fn sign_hex(secret_key_hex: String, message: Vec<u8>) -> Result<String, String> {
-    let secret_bytes =
-        hex::decode(secret_key_hex).map_err(|e| format!("Invalid secret key hex: {}", e))?;
-    let secret_key_array: [u8; SIGNING_SECRET_KEY_BYTES] =
-        secret_bytes.try_into().map_err(|_| {
-            format!(
-                "Invalid secret key length, expected {}",
-                SIGNING_SECRET_KEY_BYTES
-            )
-        })?;
-
-    // Construct using the ::from_bytes constructor from core-crypto
-    let core_secret_key = SigningSecretKey::from_bytes(secret_key_array);
+    let core_secret_key = parse_signing_secret_key(secret_key_hex)?;
 
     sign(&core_secret_key, &message)
         .map(|sig| hex::encode(sig.as_bytes())) // Use as_bytes()
         .map_err(map_crypto_err)
 }
 
+// `strict` selects VerificationMode::Strict (rejects weak keys and non-canonical signatures);
+// it defaults to the original permissive check.
 #[command]
 pub fn verify_hex(
     public_key_hex: String,
     message: Vec<u8>,
     signature_hex: String,
+    strict: Option<bool>,
 ) -> Result<(), String> {
-    let public_bytes =
-        hex::decode(public_key_hex).map_err(|e| format!("Invalid public key hex: {}", e))?;
+    let core_public_key = parse_signing_public_key(public_key_hex)?;
     let signature_bytes =
         hex::decode(signature_hex).map_err(|e| format!("Invalid signature hex: {}", e))?;
+    let signature_array: [u8; SIGNATURE_BYTES] = signature_bytes
+        .try_into()
+        .map_err(|_| format!("Invalid signature length, expected {}", SIGNATURE_BYTES))?;
 
-    let public_key_array: [u8; SIGNING_PUBLIC_KEY_BYTES] =
-        public_bytes.try_into().map_err(|_| {
+    // Construct using the ::try_from_bytes constructor (which validates)
+    let core_signature = Signature::try_from_bytes(&signature_array).map_err(map_crypto_err)?;
+
+    verify_with_mode(&core_public_key, &message, &core_signature, verification_mode(strict))
+        .map_err(map_crypto_err)
+}
+
+// One entry for verify_batch_hex
+#[derive(Debug, Deserialize)]
+pub struct SignedItemHex {
+    pub public_key_hex: String,
+    pub message: Vec<u8>,
+    pub signature_hex: String,
+}
+
+// Verifies many signatures in one IPC round-trip (e.g. a whole manifest or ledger).
+// Returns the indices of the items that failed verification; an empty list means all are valid.
+// Malformed hex or wrong lengths are reported as an error for the whole call.
+// `strict` works as for verify_hex and defaults to the permissive check.
+#[command]
+pub fn verify_batch_hex(items: Vec<SignedItemHex>, strict: Option<bool>) -> Result<Vec<usize>, String> {
+    let mut parsed = Vec::with_capacity(items.len());
+    let mut failed = Vec::new();
+    for (index, item) in items.iter().enumerate() {
+        let public_key_array = decode_signing_public_key(&item.public_key_hex)
+            .map_err(|e| format!("Item {}: {}", index, e))?;
+        let signature_bytes = hex::decode(&item.signature_hex)
+            .map_err(|e| format!("Item {}: invalid signature hex: {}", index, e))?;
+        let signature_array: [u8; SIGNATURE_BYTES] = signature_bytes.try_into().map_err(|_| {
             format!(
-                "Invalid public key length, expected {}",
-                SIGNING_PUBLIC_KEY_BYTES
+                "Item {}: invalid signature length, expected {}",
+                index, SIGNATURE_BYTES
             )
         })?;
+
+        // Keys or signatures that don't decode can't be valid, so they count as failures
+        match (
+            SigningPublicKey::try_from_bytes(&public_key_array),
+            Signature::try_from_bytes(&signature_array),
+        ) {
+            (Ok(public_key), Ok(signature)) => parsed.push((index, public_key, signature)),
+            _ => failed.push(index),
+        }
+    }
+
+    let batch: Vec<(&SigningPublicKey, &[u8], &Signature)> = parsed
+        .iter()
+        .map(|(index, public_key, signature)| (public_key, items[*index].message.as_slice(), signature))
+        .collect();
+    match verify_batch(&batch, verification_mode(strict)) {
+        Ok(()) => {}
+        // Indices from verify_batch refer to the parsed subset
+        Err(CryptoError::BatchVerificationFailed(indices)) => {
+            failed.extend(indices.into_iter().map(|i| parsed[i].0))
+        }
+        Err(e) => return Err(map_crypto_err(e)),
+    }
+    failed.sort_unstable();
+    Ok(failed)
+}
+
+// Signs `message` for one of the registered purposes (e.g. "transactable-key-token").
+// The signature only verifies with verify_with_context_hex under the same context label.
+#[command]
+pub fn sign_with_context_hex(
+    secret_key_hex: String,
+    context: String,
+    message: Vec<u8>,
+) -> Result<String, String> {
+    let context = SigningContext::from_label(&context).map_err(map_crypto_err)?;
+    let core_secret_key = parse_signing_secret_key(secret_key_hex)?;
+
+    sign_with_context(&core_secret_key, context, &message)
+        .map(|sig| hex::encode(sig.as_bytes()))
+        .map_err(map_crypto_err)
+}
+
+// Verifies a signature from sign_with_context_hex. Signatures made for a different context
+// (or with plain sign_hex) are rejected.
+#[command]
+pub fn verify_with_context_hex(
+    public_key_hex: String,
+    context: String,
+    message: Vec<u8>,
+    signature_hex: String,
+) -> Result<(), String> {
+    let context = SigningContext::from_label(&context).map_err(map_crypto_err)?;
+    let core_public_key = parse_signing_public_key(public_key_hex)?;
+    let signature_bytes =
+        hex::decode(signature_hex).map_err(|e| format!("Invalid signature hex: {}", e))?;
     let signature_array: [u8; SIGNATURE_BYTES] = signature_bytes
         .try_into()
         .map_err(|_| format!("Invalid signature length, expected {}", SIGNATURE_BYTES))?;
 
-    // Construct using the ::try_from_bytes constructors (which validate)
-    let core_public_key =
-        SigningPublicKey::try_from_bytes(&public_key_array).map_err(map_crypto_err)?;
     let core_signature = Signature::try_from_bytes(&signature_array).map_err(map_crypto_err)?;
 
-    verify(&core_public_key, &message, &core_signature).map_err(map_crypto_err)
+    verify_with_context(&core_public_key, context, &message, &core_signature)
+        .map_err(map_crypto_err)
 }
 
 #[command]
@@ -84,6 +175,7 @@ pub fn encrypt_symmetric_hex(
     key_hex: String,
     nonce_hex: String,
     plaintext: Vec<u8>,
+    associated_data: Option<Vec<u8>>,
 ) -> Result<Vec<u8>, String> {
     let key_bytes = hex::decode(key_hex).map_err(|e| format!("Invalid key hex: {}", e))?;
     let nonce_bytes = hex::decode(nonce_hex).map_err(|e| format!("Invalid nonce hex: {}", e))?;
@@ -95,8 +187,14 @@ pub fn encrypt_symmetric_hex(
         .try_into()
         .map_err(|_| format!("Invalid nonce length, expected {}", NONCE_BYTES))?;
 
-    // encrypt_symmetric expects arrays directly, no wrapper types
-    encrypt_symmetric(&key_array, &plaintext, &nonce_array, None).map_err(map_crypto_err)
+    // The key is wrapped in SymKey so it is wiped once encryption is done
+    encrypt_symmetric(
+        &SymKey::from_bytes(key_array),
+        &plaintext,
+        &nonce_array,
+        associated_data.as_deref(),
+    )
+    .map_err(map_crypto_err)
 }
 
 #[command]
@@ -104,6 +202,7 @@ pub fn decrypt_symmetric_hex(
     key_hex: String,
     nonce_hex: String,
     ciphertext: Vec<u8>,
+    associated_data: Option<Vec<u8>>,
 ) -> Result<Vec<u8>, String> {
     let key_bytes = hex::decode(key_hex).map_err(|e| format!("Invalid key hex: {}", e))?;
     let nonce_bytes = hex::decode(nonce_hex).map_err(|e| format!("Invalid nonce hex: {}", e))?;
@@ -115,6 +214,539 @@ pub fn decrypt_symmetric_hex(
         .try_into()
         .map_err(|_| format!("Invalid nonce length, expected {}", NONCE_BYTES))?;
 
-    // decrypt_symmetric expects arrays directly, no wrapper types
-    decrypt_symmetric(&key_array, &ciphertext, &nonce_array, None).map_err(map_crypto_err)
+    // The key is wrapped in SymKey so it is wiped once decryption is done
+    // The associated data must match exactly what was passed to encrypt_symmetric_hex
+    decrypt_symmetric(
+        &SymKey::from_bytes(key_array),
+        &ciphertext,
+        &nonce_array,
+        associated_data.as_deref(),
+    )
+    .map_err(map_crypto_err)
+}
+
+#[command]
+pub fn generate_xnonce_hex() -> Result<String, String> {
+    let nonce = generate_xnonce();
+    Ok(hex::encode(nonce))
+}
+
+#[command]
+pub fn encrypt_symmetric_xchacha_hex(
+    key_hex: String,
+    nonce_hex: String,
+    plaintext: Vec<u8>,
+    associated_data: Option<Vec<u8>>,
+) -> Result<Vec<u8>, String> {
+    let key_bytes = hex::decode(key_hex).map_err(|e| format!("Invalid key hex: {}", e))?;
+    let nonce_bytes = hex::decode(nonce_hex).map_err(|e| format!("Invalid nonce hex: {}", e))?;
+
+    let key_array: [u8; SYMMETRIC_KEY_BYTES] = key_bytes
+        .try_into()
+        .map_err(|_| format!("Invalid key length, expected {}", SYMMETRIC_KEY_BYTES))?;
+    let nonce_array: [u8; XNONCE_BYTES] = nonce_bytes
+        .try_into()
+        .map_err(|_| format!("Invalid nonce length, expected {}", XNONCE_BYTES))?;
+
+    encrypt_symmetric_xchacha(
+        &SymKey::from_bytes(key_array),
+        &plaintext,
+        &nonce_array,
+        associated_data.as_deref(),
+    )
+    .map_err(map_crypto_err)
+}
+
+#[command]
+pub fn decrypt_symmetric_xchacha_hex(
+    key_hex: String,
+    nonce_hex: String,
+    ciphertext: Vec<u8>,
+    associated_data: Option<Vec<u8>>,
+) -> Result<Vec<u8>, String> {
+    let key_bytes = hex::decode(key_hex).map_err(|e| format!("Invalid key hex: {}", e))?;
+    let nonce_bytes = hex::decode(nonce_hex).map_err(|e| format!("Invalid nonce hex: {}", e))?;
+
+    let key_array: [u8; SYMMETRIC_KEY_BYTES] = key_bytes
+        .try_into()
+        .map_err(|_| format!("Invalid key length, expected {}", SYMMETRIC_KEY_BYTES))?;
+    let nonce_array: [u8; XNONCE_BYTES] = nonce_bytes
+        .try_into()
+        .map_err(|_| format!("Invalid nonce length, expected {}", XNONCE_BYTES))?;
+
+    decrypt_symmetric_xchacha(
+        &SymKey::from_bytes(key_array),
+        &ciphertext,
+        &nonce_array,
+        associated_data.as_deref(),
+    )
+    .map_err(map_crypto_err)
+}
+
+// Streams `input_path` through encrypt_stream into `output_path`, so files of any size can be
+// encrypted without loading them into memory. Paths are the ones returned by the fs/dialog plugins.
+#[command]
+pub fn encrypt_file(
+    key_hex: String,
+    input_path: String,
+    output_path: String,
+    associated_data: Option<Vec<u8>>,
+) -> Result<u64, String> {
+    let key_bytes = hex::decode(key_hex).map_err(|e| format!("Invalid key hex: {}", e))?;
+    let key_array: [u8; SYMMETRIC_KEY_BYTES] = key_bytes
+        .try_into()
+        .map_err(|_| format!("Invalid key length, expected {}", SYMMETRIC_KEY_BYTES))?;
+
+    let mut reader = BufReader::new(
+        File::open(&input_path).map_err(|e| format!("Failed to open input file: {}", e))?,
+    );
+    let mut writer = BufWriter::new(
+        File::create(&output_path).map_err(|e| format!("Failed to create output file: {}", e))?,
+    );
+
+    encrypt_stream(
+        &SymKey::from_bytes(key_array),
+        &mut reader,
+        &mut writer,
+        associated_data.as_deref(),
+    )
+    .map_err(map_crypto_err)
+}
+
+// Streams `input_path` through decrypt_stream into `output_path`. If the ciphertext fails to
+// authenticate at any point the partially written output file is removed.
+#[command]
+pub fn decrypt_file(
+    key_hex: String,
+    input_path: String,
+    output_path: String,
+    associated_data: Option<Vec<u8>>,
+) -> Result<u64, String> {
+    let key_bytes = hex::decode(key_hex).map_err(|e| format!("Invalid key hex: {}", e))?;
+    let key_array: [u8; SYMMETRIC_KEY_BYTES] = key_bytes
+        .try_into()
+        .map_err(|_| format!("Invalid key length, expected {}", SYMMETRIC_KEY_BYTES))?;
+
+    let mut reader = BufReader::new(
+        File::open(&input_path).map_err(|e| format!("Failed to open input file: {}", e))?,
+    );
+    let mut writer = BufWriter::new(
+        File::create(&output_path).map_err(|e| format!("Failed to create output file: {}", e))?,
+    );
+
+    let result = decrypt_stream(
+        &SymKey::from_bytes(key_array),
+        &mut reader,
+        &mut writer,
+        associated_data.as_deref(),
+    );
+    if result.is_err() {
+        drop(writer);
+        // Never leave unauthenticated plaintext behind
+        let _ = std::fs::remove_file(&output_path);
+    }
+    result.map_err(map_crypto_err)
+}
+
+// Signs the file at `path` with Ed25519ph, streaming it through the hasher so multi-GB files
+// never have to fit in memory. Returns the signature hex; check it with verify_file.
+#[command]
+pub fn sign_file(secret_key_hex: String, path: String) -> Result<String, String> {
+    let secret_key = parse_signing_secret_key(secret_key_hex)?;
+    let mut reader =
+        BufReader::new(File::open(&path).map_err(|e| format!("Failed to open file: {}", e))?);
+    let mut hasher = Ed25519phHasher::new();
+    hasher.update_reader(&mut reader).map_err(map_crypto_err)?;
+
+    sign_prehashed(&secret_key, hasher)
+        .map(|sig| hex::encode(sig.as_bytes()))
+        .map_err(map_crypto_err)
+}
+
+// Verifies a signature from sign_file against the file at `path`, streaming it like sign_file.
+#[command]
+pub fn verify_file(
+    public_key_hex: String,
+    path: String,
+    signature_hex: String,
+) -> Result<(), String> {
+    let core_public_key = parse_signing_public_key(public_key_hex)?;
+    let signature_bytes =
+        hex::decode(signature_hex).map_err(|e| format!("Invalid signature hex: {}", e))?;
+    let signature_array: [u8; SIGNATURE_BYTES] = signature_bytes
+        .try_into()
+        .map_err(|_| format!("Invalid signature length, expected {}", SIGNATURE_BYTES))?;
+
+    let core_signature = Signature::try_from_bytes(&signature_array).map_err(map_crypto_err)?;
+
+    let mut reader =
+        BufReader::new(File::open(&path).map_err(|e| format!("Failed to open file: {}", e))?);
+    let mut hasher = Ed25519phHasher::new();
+    hasher.update_reader(&mut reader).map_err(map_crypto_err)?;
+
+    verify_prehashed(&core_public_key, hasher, &core_signature).map_err(map_crypto_err)
+}
+
+// Writes `public_key_hex` as a minisign public key file (for `minisign -V -p <file>`).
+// Returns the key id as minisign displays it.
+#[command]
+pub fn export_minisign_public_key(public_key_hex: String, output_path: String) -> Result<String, String> {
+    let public_key = MinisignPublicKey::new(parse_signing_public_key(public_key_hex)?);
+
+    std::fs::write(&output_path, public_key.to_file_string())
+        .map_err(|e| format!("Failed to write public key file: {}", e))?;
+    Ok(public_key.key_id.to_hex())
+}
+
+// Writes `secret_key_hex` as an unencrypted minisign secret key file (like `minisign -G -W`).
+// The file is not password protected, so it should go somewhere the user controls. An existing
+// file is never overwritten, and on Unix the new file is readable by the owner only (0600).
+#[command]
+pub fn export_minisign_secret_key(secret_key_hex: String, output_path: String) -> Result<String, String> {
+    let secret_key = MinisignSecretKey::new(parse_signing_secret_key(secret_key_hex)?);
+
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(&output_path)
+        .map_err(|e| format!("Failed to create secret key file: {}", e))?;
+    file.write_all(secret_key.to_file_string().as_bytes())
+        .map_err(|e| format!("Failed to write secret key file: {}", e))?;
+    Ok(secret_key.key_id.to_hex())
+}
+
+// Signs the file at `path` like `minisign -S`, writing the detached signature to `<path>.minisig`.
+// Without a `trusted_comment` the minisign default ("timestamp:<now>\tfile:<name>\thashed") is used.
+// Returns the path of the signature file.
+#[command]
+pub fn minisign_sign_file(
+    secret_key_hex: String,
+    path: String,
+    trusted_comment: Option<String>,
+) -> Result<String, String> {
+    let secret_key = MinisignSecretKey::new(parse_signing_secret_key(secret_key_hex)?);
+
+    let trusted_comment = match trusted_comment {
+        Some(comment) => comment,
+        None => {
+            let timestamp = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            let file_name = std::path::Path::new(&path)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            format!("timestamp:{}\tfile:{}\thashed", timestamp, file_name)
+        }
+    };
+
+    let mut reader =
+        BufReader::new(File::open(&path).map_err(|e| format!("Failed to open file: {}", e))?);
+    let signature =
+        minisign_sign(&secret_key, &mut reader, &trusted_comment).map_err(map_crypto_err)?;
+
+    let signature_path = format!("{}.minisig", path);
+    std::fs::write(&signature_path, signature.to_file_string())
+        .map_err(|e| format!("Failed to write signature file: {}", e))?;
+    Ok(signature_path)
+}
+
+// Verifies the file at `path` like `minisign -V`, using a minisign public key file and the
+// detached signature at `signature_path` (default `<path>.minisig`). Returns the trusted comment,
+// which is only meaningful once verification has succeeded.
+#[command]
+pub fn minisign_verify_file(
+    public_key_path: String,
+    path: String,
+    signature_path: Option<String>,
+) -> Result<String, String> {
+    let public_key_file = std::fs::read_to_string(&public_key_path)
+        .map_err(|e| format!("Failed to read public key file: {}", e))?;
+    let public_key = MinisignPublicKey::from_file_str(&public_key_file).map_err(map_crypto_err)?;
+
+    let signature_path = signature_path.unwrap_or_else(|| format!("{}.minisig", path));
+    let signature_file = std::fs::read_to_string(&signature_path)
+        .map_err(|e| format!("Failed to read signature file: {}", e))?;
+    let signature = MinisignSignature::from_file_str(&signature_file).map_err(map_crypto_err)?;
+
+    let mut reader =
+        BufReader::new(File::open(&path).map_err(|e| format!("Failed to open file: {}", e))?);
+    minisign_verify(&public_key, &mut reader, &signature).map_err(map_crypto_err)?;
+    Ok(signature.trusted_comment)
+}
+
+// Encrypts into a self-describing envelope (XChaCha20-Poly1305 with a fresh random nonce).
+// The returned bytes carry the algorithm, key id, nonce and associated data, so only the key
+// is needed to decrypt them later with open_envelope_hex.
+#[command]
+pub fn seal_envelope_hex(
+    key_hex: String,
+    plaintext: Vec<u8>,
+    associated_data: Option<Vec<u8>>,
+) -> Result<Vec<u8>, String> {
+    let key_bytes = hex::decode(key_hex).map_err(|e| format!("Invalid key hex: {}", e))?;
+    let key_array: [u8; SYMMETRIC_KEY_BYTES] = key_bytes
+        .try_into()
+        .map_err(|_| format!("Invalid key length, expected {}", SYMMETRIC_KEY_BYTES))?;
+
+    seal_envelope(
+        &SymKey::from_bytes(key_array),
+        SymmetricAlgorithm::XChaCha20Poly1305,
+        &plaintext,
+        associated_data.as_deref(),
+    )
+    .map(|envelope| envelope.to_bytes())
+    .map_err(map_crypto_err)
+}
+
+#[command]
+pub fn open_envelope_hex(key_hex: String, envelope: Vec<u8>) -> Result<Vec<u8>, String> {
+    let key_bytes = hex::decode(key_hex).map_err(|e| format!("Invalid key hex: {}", e))?;
+    let key_array: [u8; SYMMETRIC_KEY_BYTES] = key_bytes
+        .try_into()
+        .map_err(|_| format!("Invalid key length, expected {}", SYMMETRIC_KEY_BYTES))?;
+
+    let parsed = Envelope::parse(&envelope).map_err(map_crypto_err)?;
+    open_envelope(&SymKey::from_bytes(key_array), &parsed).map_err(map_crypto_err)
+}
+
+// Wraps a 32-byte symmetric (content) key under a key-encryption key with AES-256-KW.
+// The returned bytes record the key kind and the KEK's id; unwrap with unwrap_symmetric_key_hex.
+#[command]
+pub fn wrap_symmetric_key_hex(kek_hex: String, key_hex: String) -> Result<Vec<u8>, String> {
+    let kek_bytes = hex::decode(kek_hex).map_err(|e| format!("Invalid KEK hex: {}", e))?;
+    let kek_array: [u8; SYMMETRIC_KEY_BYTES] = kek_bytes
+        .try_into()
+        .map_err(|_| format!("Invalid KEK length, expected {}", SYMMETRIC_KEY_BYTES))?;
+    let key_bytes = hex::decode(key_hex).map_err(|e| format!("Invalid key hex: {}", e))?;
+    let key_array: [u8; SYMMETRIC_KEY_BYTES] = key_bytes
+        .try_into()
+        .map_err(|_| format!("Invalid key length, expected {}", SYMMETRIC_KEY_BYTES))?;
+
+    wrap_key(&SymKey::from_bytes(kek_array), &SymKey::from_bytes(key_array))
+        .map(|wrapped| wrapped.to_bytes())
+        .map_err(map_crypto_err)
+}
+
+#[command]
+pub fn unwrap_symmetric_key_hex(kek_hex: String, wrapped_key: Vec<u8>) -> Result<String, String> {
+    let kek_bytes = hex::decode(kek_hex).map_err(|e| format!("Invalid KEK hex: {}", e))?;
+    let kek_array: [u8; SYMMETRIC_KEY_BYTES] = kek_bytes
+        .try_into()
+        .map_err(|_| format!("Invalid KEK length, expected {}", SYMMETRIC_KEY_BYTES))?;
+
+    let parsed = WrappedKey::parse(&wrapped_key).map_err(map_crypto_err)?;
+    let key: SymKey = unwrap_key(&SymKey::from_bytes(kek_array), &parsed).map_err(map_crypto_err)?;
+    Ok(hex::encode(key.expose_secret()))
+}
+
+#[command]
+pub fn generate_key_exchange_keypair_hex() -> Result<(String, String), String> {
+    let (secret_key, public_key) = generate_key_exchange_keypair();
+    // As with signing keys, the frontend is responsible for protecting the secret half.
+    Ok((
+        hex::encode(secret_key.expose_secret()),
+        hex::encode(public_key.as_bytes()),
+    ))
+}
+
+// Encrypts `plaintext` to the holder of an X25519 public key, no handshake required
+#[command]
+pub fn seal_hex(recipient_public_key_hex: String, plaintext: Vec<u8>) -> Result<Vec<u8>, String> {
+    let recipient = parse_key_exchange_public_key(recipient_public_key_hex)?;
+    sealed_box::seal(&recipient, &plaintext).map_err(map_crypto_err)
+}
+
+#[command]
+pub fn open_sealed_hex(secret_key_hex: String, sealed: Vec<u8>) -> Result<Vec<u8>, String> {
+    let recipient_secret = parse_key_exchange_secret_key(secret_key_hex)?;
+    sealed_box::open(&recipient_secret, &sealed).map_err(map_crypto_err)
+}
+
+fn parse_key_exchange_public_key(public_key_hex: String) -> Result<KeyExchangePublicKey, String> {
+    let public_bytes =
+        hex::decode(public_key_hex).map_err(|e| format!("Invalid public key hex: {}", e))?;
+    let public_key_array: [u8; KEY_EXCHANGE_PUBLIC_KEY_BYTES] =
+        public_bytes.try_into().map_err(|_| {
+            format!(
+                "Invalid public key length, expected {}",
+                KEY_EXCHANGE_PUBLIC_KEY_BYTES
+            )
+        })?;
+    Ok(KeyExchangePublicKey::from_bytes(public_key_array))
+}
+
+fn parse_key_exchange_secret_key(secret_key_hex: String) -> Result<KeyExchangeSecretKey, String> {
+    let secret_bytes =
+        hex::decode(secret_key_hex).map_err(|e| format!("Invalid secret key hex: {}", e))?;
+    let secret_key_array: [u8; KEY_EXCHANGE_SECRET_KEY_BYTES] =
+        secret_bytes.try_into().map_err(|_| {
+            format!(
+                "Invalid secret key length, expected {}",
+                KEY_EXCHANGE_SECRET_KEY_BYTES
+            )
+        })?;
+    Ok(KeyExchangeSecretKey::from_bytes(secret_key_array))
+}
+
+// Both halves of a PSK must be given together; neither means no PSK
+fn hpke_psk<'a>(
+    psk: &'a Option<Vec<u8>>,
+    psk_id: &'a Option<Vec<u8>>,
+) -> Result<Option<hpke::PskInput<'a>>, String> {
+    match (psk, psk_id) {
+        (Some(psk), Some(psk_id)) => Ok(Some(hpke::PskInput { psk, psk_id })),
+        (None, None) => Ok(None),
+        _ => Err("psk and psk_id must be provided together".to_string()),
+    }
+}
+
+// Single-shot HPKE (RFC 9180) encryption. Supplying `sender_secret_key_hex` selects auth mode and
+// supplying `psk`/`psk_id` selects PSK mode (both selects auth-PSK). Returns `(enc_hex, ciphertext)`;
+// the recipient needs both.
+#[command]
+pub fn hpke_seal_hex(
+    recipient_public_key_hex: String,
+    info: Vec<u8>,
+    associated_data: Vec<u8>,
+    plaintext: Vec<u8>,
+    sender_secret_key_hex: Option<String>,
+    psk: Option<Vec<u8>>,
+    psk_id: Option<Vec<u8>>,
+) -> Result<(String, Vec<u8>), String> {
+    let recipient = parse_key_exchange_public_key(recipient_public_key_hex)?;
+    let sender_secret = sender_secret_key_hex
+        .map(parse_key_exchange_secret_key)
+        .transpose()?;
+    let psk = hpke_psk(&psk, &psk_id)?;
+
+    let mode = match (&sender_secret, psk) {
+        (None, None) => hpke::SenderMode::Base,
+        (None, Some(psk)) => hpke::SenderMode::Psk(psk),
+        (Some(sender), None) => hpke::SenderMode::Auth(sender),
+        (Some(sender), Some(psk)) => hpke::SenderMode::AuthPsk(sender, psk),
+    };
+
+    let (enc, ciphertext) = hpke::seal(mode, &recipient, &info, &associated_data, &plaintext)
+        .map_err(map_crypto_err)?;
+    Ok((hex::encode(enc), ciphertext))
+}
+
+// Opens a ciphertext produced by hpke_seal_hex. The mode arguments must match the sender's:
+// `sender_public_key_hex` for auth mode, `psk`/`psk_id` for PSK mode.
+#[command]
+#[allow(clippy::too_many_arguments)]
+pub fn hpke_open_hex(
+    recipient_secret_key_hex: String,
+    enc_hex: String,
+    info: Vec<u8>,
+    associated_data: Vec<u8>,
+    ciphertext: Vec<u8>,
+    sender_public_key_hex: Option<String>,
+    psk: Option<Vec<u8>>,
+    psk_id: Option<Vec<u8>>,
+) -> Result<Vec<u8>, String> {
+    let recipient_secret = parse_key_exchange_secret_key(recipient_secret_key_hex)?;
+    let enc_bytes = hex::decode(enc_hex).map_err(|e| format!("Invalid enc hex: {}", e))?;
+    let enc: hpke::Encapsulation = enc_bytes
+        .try_into()
+        .map_err(|_| format!("Invalid enc length, expected {}", hpke::HPKE_ENC_BYTES))?;
+    let sender_public = sender_public_key_hex
+        .map(parse_key_exchange_public_key)
+        .transpose()?;
+    let psk = hpke_psk(&psk, &psk_id)?;
+
+    let mode = match (&sender_public, psk) {
+        (None, None) => hpke::RecipientMode::Base,
+        (None, Some(psk)) => hpke::RecipientMode::Psk(psk),
+        (Some(sender), None) => hpke::RecipientMode::Auth(sender),
+        (Some(sender), Some(psk)) => hpke::RecipientMode::AuthPsk(sender, psk),
+    };
+
+    hpke::open(mode, &enc, &recipient_secret, &info, &associated_data, &ciphertext)
+        .map_err(map_crypto_err)
+}
+
+// The decoded bytes are wiped once the key has been built
+fn parse_signing_secret_key(secret_key_hex: String) -> Result<SigningSecretKey, String> {
+    let secret_key_hex = Zeroizing::new(secret_key_hex);
+    let secret_bytes = Zeroizing::new(
+        hex::decode(secret_key_hex.as_str()).map_err(|e| format!("Invalid secret key hex: {}", e))?,
+    );
+    if secret_bytes.len() != SIGNING_SECRET_KEY_BYTES {
+        return Err(format!(
+            "Invalid secret key length, expected {}",
+            SIGNING_SECRET_KEY_BYTES
+        ));
+    }
+    let mut secret_key_array = Zeroizing::new([0u8; SIGNING_SECRET_KEY_BYTES]);
+    secret_key_array.copy_from_slice(&secret_bytes);
+    Ok(SigningSecretKey::from_bytes(*secret_key_array))
+}
+
+fn parse_signing_public_key(public_key_hex: String) -> Result<SigningPublicKey, String> {
+    SigningPublicKey::try_from_bytes(&decode_signing_public_key(&public_key_hex)?).map_err(map_crypto_err)
+}
+
+// Hex and length checks of parse_signing_public_key, for callers that handle invalid points
+// themselves
+fn decode_signing_public_key(public_key_hex: &str) -> Result<[u8; SIGNING_PUBLIC_KEY_BYTES], String> {
+    let public_bytes =
+        hex::decode(public_key_hex).map_err(|e| format!("Invalid public key hex: {}", e))?;
+    public_bytes.try_into().map_err(|_| {
+        format!(
+            "Invalid public key length, expected {}",
+            SIGNING_PUBLIC_KEY_BYTES
+        )
+    })
+}
+
+// Every rendering of one fingerprint, for the UI to show whichever fits
+#[derive(Debug, Serialize)]
+pub struct FingerprintDisplay {
+    pub version: u8,
+    pub hex: String,
+    pub base32: String,
+    pub safety_phrase: String,
+}
+
+impl From<Fingerprint> for FingerprintDisplay {
+    fn from(fingerprint: Fingerprint) -> Self {
+        Self {
+            version: fingerprint.version(),
+            hex: hex::encode(fingerprint.as_bytes()),
+            base32: fingerprint.to_base32_grouped(),
+            safety_phrase: fingerprint.to_safety_phrase(),
+        }
+    }
+}
+
+#[command]
+pub fn signing_key_fingerprint_hex(public_key_hex: String) -> Result<FingerprintDisplay, String> {
+    let public_key = parse_signing_public_key(public_key_hex)?;
+    Ok(signing_key_fingerprint(&public_key).into())
+}
+
+#[command]
+pub fn key_exchange_key_fingerprint_hex(public_key_hex: String) -> Result<FingerprintDisplay, String> {
+    let public_key = parse_key_exchange_public_key(public_key_hex)?;
+    Ok(key_exchange_key_fingerprint(&public_key).into())
+}
+
+// Safety number for two users' signing (identity) keys. Both sides get the same number, so they
+// can compare it in person or over a call to confirm neither key was substituted.
+#[command]
+pub fn safety_number_hex(our_public_key_hex: String, their_public_key_hex: String) -> Result<String, String> {
+    let ours = parse_signing_public_key(our_public_key_hex)?;
+    let theirs = parse_signing_public_key(their_public_key_hex)?;
+    Ok(safety_number(
+        &signing_key_fingerprint(&ours),
+        &signing_key_fingerprint(&theirs),
+    ))
 }
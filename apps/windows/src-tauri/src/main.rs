@@ -21,12 +21,40 @@ fn main() {
             crypto_commands::generate_signing_keypair_hex,
             crypto_commands::sign_hex,
             crypto_commands::verify_hex,
+            crypto_commands::verify_batch_hex,
+            crypto_commands::sign_with_context_hex,
+            crypto_commands::verify_with_context_hex,
             crypto_commands::generate_nonce_hex,
             crypto_commands::encrypt_symmetric_hex,
             crypto_commands::decrypt_symmetric_hex,
+            crypto_commands::generate_xnonce_hex,
+            crypto_commands::encrypt_symmetric_xchacha_hex,
+            crypto_commands::decrypt_symmetric_xchacha_hex,
+            crypto_commands::encrypt_file,
+            crypto_commands::decrypt_file,
+            crypto_commands::sign_file,
+            crypto_commands::verify_file,
+            crypto_commands::export_minisign_public_key,
+            crypto_commands::export_minisign_secret_key,
+            crypto_commands::minisign_sign_file,
+            crypto_commands::minisign_verify_file,
+            crypto_commands::seal_envelope_hex,
+            crypto_commands::open_envelope_hex,
+            crypto_commands::wrap_symmetric_key_hex,
+            crypto_commands::unwrap_symmetric_key_hex,
+            crypto_commands::generate_key_exchange_keypair_hex,
+            crypto_commands::seal_hex,
+            crypto_commands::open_sealed_hex,
+            crypto_commands::hpke_seal_hex,
+            crypto_commands::hpke_open_hex,
+            crypto_commands::signing_key_fingerprint_hex,
+            crypto_commands::key_exchange_key_fingerprint_hex,
+            crypto_commands::safety_number_hex,
             // Wallet commands
             wallet_commands::import_mnemonic,
-            wallet_commands::export_mnemonic
+            wallet_commands::export_mnemonic,
+            wallet_commands::get_key_exchange_public_key,
+            wallet_commands::get_identity_public_key
         ])
         .setup(|app| Ok(()))
         .run(tauri::generate_context!())
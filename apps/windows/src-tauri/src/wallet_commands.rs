@@ -1,8 +1,13 @@
-use core_crypto::{mnemonic_to_seed, validate_mnemonic, CryptoError};
+use core_crypto::{
+    derive_identity_keypair, derive_key_exchange_keypair, derive_root_identity_secret,
+    mnemonic_to_seed_with_passphrase, validate_mnemonic, CryptoError, IdentityScheme, MasterSeed,
+    Slip10Path,
+};
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, Mutex};
 use storage_interface::{SecureStorage, StorageError};
 use thiserror::Error;
+use zeroize::Zeroizing;
 
 #[derive(Debug, Serialize, Deserialize, Error)]
 pub enum MnemonicImportError {
@@ -30,11 +35,38 @@ pub enum MnemonicExportError {
     InternalError(String), // Catch-all
 }
 
+// Result of export_mnemonic. When `passphrase_protected` is set, the mnemonic alone restores a
+// different (empty) wallet and `warning` says so, for the UI to show before the user relies on it.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MnemonicExport {
+    pub mnemonic: String,
+    pub passphrase_protected: bool,
+    pub warning: Option<String>,
+}
+
+const PASSPHRASE_EXPORT_WARNING: &str = "This wallet was imported with a BIP-39 passphrase. The mnemonic alone will not restore it; you also need the passphrase, which is not stored and cannot be exported.";
+
+#[derive(Debug, Serialize, Deserialize, Error)]
+pub enum WalletKeyError {
+    #[error("Wallet not initialized or seed not found")]
+    NotInitialized,
+    #[error("Storage layer error during key derivation: {error}")]
+    RetrievalFailed { error: String },
+    #[error("Failed to derive key from seed: {0}")]
+    DerivationFailed(String),
+}
+
+// Purpose used when the caller doesn't name an account
+pub const DEFAULT_KEY_EXCHANGE_PURPOSE: &str = "primary-key-exchange";
+// Purpose of the identity key under the HKDF scheme
+pub const DEFAULT_IDENTITY_PURPOSE: &str = "primary-chain-signing";
+
 // --- Mock Secure Storage (FOR DEVELOPMENT/TESTING ONLY) ---
 #[derive(Debug, Clone, Default)]
 pub struct MockSecureStorage {
     seed: Arc<Mutex<Option<Vec<u8>>>>,
     mnemonic: Arc<Mutex<Option<String>>>, // Add storage for mnemonic
+    uses_passphrase: Arc<Mutex<Option<bool>>>,
 }
 
 impl SecureStorage for MockSecureStorage {
@@ -94,12 +126,33 @@ impl SecureStorage for MockSecureStorage {
             }
         }
     }
+
+    fn store_passphrase_flag(&self, uses_passphrase: bool) -> Result<(), StorageError> {
+        println!("[MockStorage] Storing passphrase flag: {}", uses_passphrase);
+        let mut lock = self
+            .uses_passphrase
+            .lock()
+            .map_err(|e| StorageError::InternalError(format!("Mutex poisoned: {}", e)))?;
+        *lock = Some(uses_passphrase);
+        Ok(())
+    }
+
+    fn retrieve_passphrase_flag(&self) -> Result<bool, StorageError> {
+        let lock = self
+            .uses_passphrase
+            .lock()
+            .map_err(|e| StorageError::InternalError(format!("Mutex poisoned: {}", e)))?;
+        Ok(lock.unwrap_or(false))
+    }
 }
 // --- End Mock Secure Storage ---
 
+// `passphrase` is the optional BIP-39 passphrase ("25th word"). It only feeds the seed derivation:
+// it is never logged or stored, only a flag recording that one was used.
 #[tauri::command]
 pub async fn import_mnemonic(
     mnemonic: String,
+    passphrase: Option<String>,
     storage: tauri::State<'_, MockSecureStorage>, // Inject state
 ) -> Result<(), MnemonicImportError> {
     println!(
@@ -115,22 +168,16 @@ pub async fn import_mnemonic(
     println!("[Rust Backend] Mnemonic validated.");
 
     // [2.2.2] Use core-crypto (BIP-39 logic) to derive the master seed.
-    let seed_array = mnemonic_to_seed(&mnemonic)
+    // The MasterSeed is wiped from memory when it goes out of scope at the end of this command.
+    // An empty passphrase is the same as none, so don't flag it
+    let passphrase = passphrase.filter(|p| !p.is_empty());
+    let seed = mnemonic_to_seed_with_passphrase(&mnemonic, passphrase.as_deref().unwrap_or_default())
         .map_err(|e| MnemonicImportError::InternalError(format!("Failed to derive seed: {}", e)))?;
-    println!("[Rust Backend] Seed derived ({} bytes).", seed_array.len());
+    println!("[Rust Backend] Seed derived ({} bytes).", seed.expose_secret().len());
 
     // [2.2.3] Call the storage-interface function to securely store BOTH the seed and the original mnemonic.
-    storage
-        .store_seed(&seed_array)
-        .map_err(|e| MnemonicImportError::StorageFailed {
-            error: e.to_string(),
-        })?;
-    storage
-        .store_mnemonic(&mnemonic)
-        .map_err(|e| MnemonicImportError::StorageFailed {
-            error: e.to_string(),
-        })?;
-    println!("[Rust Backend] Seed and mnemonic stored via interface.");
+    store_imported_wallet(&*storage, &mnemonic, &seed, passphrase.is_some())?;
+    println!("[Rust Backend] Seed, mnemonic and passphrase flag stored via interface.");
 
     println!("[Rust Backend] import_mnemonic processed successfully.");
     Ok(())
@@ -139,7 +186,7 @@ pub async fn import_mnemonic(
 #[tauri::command]
 pub async fn export_mnemonic(
     storage: tauri::State<'_, MockSecureStorage>, // Inject state
-) -> Result<String, MnemonicExportError> {
+) -> Result<MnemonicExport, MnemonicExportError> {
     println!("[Rust Backend] Received export_mnemonic command.");
 
     // [2.2.5] Implement necessary security checks (e.g., password confirmation).
@@ -157,13 +204,111 @@ pub async fn export_mnemonic(
                 error: e.to_string(),
             })?;
 
-    match maybe_mnemonic {
-        Some(phrase) => {
-            println!("[Rust Backend] export_mnemonic processed successfully.");
-            Ok(phrase)
+    let phrase = maybe_mnemonic.ok_or(MnemonicExportError::NotInitialized)?;
+    let passphrase_protected =
+        storage
+            .retrieve_passphrase_flag()
+            .map_err(|e| MnemonicExportError::RetrievalFailed {
+                error: e.to_string(),
+            })?;
+
+    println!("[Rust Backend] export_mnemonic processed successfully.");
+    Ok(MnemonicExport {
+        mnemonic: phrase,
+        passphrase_protected,
+        warning: passphrase_protected.then(|| PASSPHRASE_EXPORT_WARNING.to_string()),
+    })
+}
+
+// Stores an imported wallet: the passphrase flag, then the mnemonic, then the seed. If a write
+// fails, the earlier ones are put back to their previous values, so the stored mnemonic and flag
+// never describe a seed other than the stored one.
+fn store_imported_wallet<S: SecureStorage + ?Sized>(
+    storage: &S,
+    mnemonic: &str,
+    seed: &MasterSeed,
+    uses_passphrase: bool,
+) -> Result<(), MnemonicImportError> {
+    let storage_failed = |e: StorageError| MnemonicImportError::StorageFailed {
+        error: e.to_string(),
+    };
+    let previous_flag = storage.retrieve_passphrase_flag().map_err(storage_failed)?;
+    let previous_mnemonic = storage.retrieve_mnemonic().map_err(storage_failed)?.map(Zeroizing::new);
+
+    storage
+        .store_passphrase_flag(uses_passphrase)
+        .map_err(storage_failed)?;
+    if let Err(e) = storage.store_mnemonic(mnemonic) {
+        let _ = storage.store_passphrase_flag(previous_flag);
+        return Err(storage_failed(e));
+    }
+    if let Err(e) = storage.store_seed(seed.expose_secret()) {
+        if let Some(previous) = &previous_mnemonic {
+            let _ = storage.store_mnemonic(previous);
         }
-        None => Err(MnemonicExportError::NotInitialized),
+        let _ = storage.store_passphrase_flag(previous_flag);
+        return Err(storage_failed(e));
     }
+    Ok(())
+}
+
+// Returns the hex X25519 public key derived from the stored seed for `purpose` (one per account).
+// The key comes from the mnemonic, so restoring the wallet restores the matching secret key.
+#[tauri::command]
+pub async fn get_key_exchange_public_key(
+    purpose: Option<String>,
+    storage: tauri::State<'_, MockSecureStorage>, // Inject state
+) -> Result<String, WalletKeyError> {
+    let purpose = purpose.unwrap_or_else(|| DEFAULT_KEY_EXCHANGE_PURPOSE.to_string());
+    println!("[Rust Backend] Received get_key_exchange_public_key command for purpose: {}", purpose);
+
+    let seed = retrieve_master_seed(&storage)?;
+    let rik = derive_root_identity_secret(&seed)
+        .map_err(|e| WalletKeyError::DerivationFailed(e.to_string()))?;
+    let (_secret_key, public_key) = derive_key_exchange_keypair(&rik, &purpose)
+        .map_err(|e| WalletKeyError::DerivationFailed(e.to_string()))?;
+
+    println!("[Rust Backend] get_key_exchange_public_key processed successfully.");
+    Ok(hex::encode(public_key.as_bytes()))
+}
+
+// Returns the hex Ed25519 identity public key. With `slip10_path` (e.g. "m/44'/501'/0'") the key
+// is derived with SLIP-0010 so it can be cross-checked in other wallets; otherwise it is the
+// HKDF identity key.
+#[tauri::command]
+pub async fn get_identity_public_key(
+    slip10_path: Option<String>,
+    storage: tauri::State<'_, MockSecureStorage>, // Inject state
+) -> Result<String, WalletKeyError> {
+    println!("[Rust Backend] Received get_identity_public_key command (SLIP-0010 path: {:?})", slip10_path);
+
+    let scheme = match slip10_path {
+        Some(path) => IdentityScheme::Slip10 {
+            path: path
+                .parse::<Slip10Path>()
+                .map_err(|e| WalletKeyError::DerivationFailed(e.to_string()))?,
+        },
+        None => IdentityScheme::Hkdf {
+            purpose: DEFAULT_IDENTITY_PURPOSE.to_string(),
+        },
+    };
+    let seed = retrieve_master_seed(&storage)?;
+    let (_secret_key, public_key) = derive_identity_keypair(&seed, &scheme)
+        .map_err(|e| WalletKeyError::DerivationFailed(e.to_string()))?;
+
+    println!("[Rust Backend] get_identity_public_key processed successfully.");
+    Ok(hex::encode(public_key.as_bytes()))
+}
+
+fn retrieve_master_seed(storage: &MockSecureStorage) -> Result<MasterSeed, WalletKeyError> {
+    let seed_bytes = storage
+        .retrieve_seed()
+        .map_err(|e| WalletKeyError::RetrievalFailed {
+            error: e.to_string(),
+        })?
+        .ok_or(WalletKeyError::NotInitialized)?;
+    // The MasterSeed takes ownership of the bytes and wipes them when dropped
+    Ok(MasterSeed::from_bytes(seed_bytes))
 }
 
 // --- Unit Tests ---
@@ -183,7 +328,7 @@ mod tests {
                 .to_string();
 
         let result =
-            import_mnemonic(valid_mnemonic.clone(), tauri::State::from(storage.clone())).await;
+            import_mnemonic(valid_mnemonic.clone(), None, tauri::State::from(storage.clone())).await;
         assert!(result.is_ok());
 
         // Verify mnemonic and seed were stored
@@ -201,7 +346,7 @@ mod tests {
         let storage = MockSecureStorage::default();
         let invalid_mnemonic = "invalid format short".to_string();
 
-        let result = import_mnemonic(invalid_mnemonic, tauri::State::from(storage.clone())).await;
+        let result = import_mnemonic(invalid_mnemonic, None, tauri::State::from(storage.clone())).await;
         assert!(result.is_err());
         match result.unwrap_err() {
             MnemonicImportError::InvalidFormat(_) => {} // Expected error
@@ -212,7 +357,81 @@ mod tests {
         assert!(storage.retrieve_seed().unwrap().is_none());
     }
 
-    // TODO: Add more import error tests (StorageFailed etc.)
+    // Fails the named storage operation and passes everything else to a MockSecureStorage
+    struct FailingStorage {
+        inner: MockSecureStorage,
+        fail_on: &'static str,
+    }
+
+    impl FailingStorage {
+        fn check(&self, operation: &str) -> Result<(), StorageError> {
+            if self.fail_on == operation {
+                Err(StorageError::InternalError(format!("injected {} failure", operation)))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    impl SecureStorage for FailingStorage {
+        fn store_seed(&self, seed: &[u8]) -> Result<(), StorageError> {
+            self.check("store_seed")?;
+            self.inner.store_seed(seed)
+        }
+
+        fn retrieve_seed(&self) -> Result<Option<Vec<u8>>, StorageError> {
+            self.check("retrieve_seed")?;
+            self.inner.retrieve_seed()
+        }
+
+        fn store_mnemonic(&self, phrase: &str) -> Result<(), StorageError> {
+            self.check("store_mnemonic")?;
+            self.inner.store_mnemonic(phrase)
+        }
+
+        fn retrieve_mnemonic(&self) -> Result<Option<String>, StorageError> {
+            self.check("retrieve_mnemonic")?;
+            self.inner.retrieve_mnemonic()
+        }
+
+        fn store_passphrase_flag(&self, uses_passphrase: bool) -> Result<(), StorageError> {
+            self.check("store_passphrase_flag")?;
+            self.inner.store_passphrase_flag(uses_passphrase)
+        }
+
+        fn retrieve_passphrase_flag(&self) -> Result<bool, StorageError> {
+            self.check("retrieve_passphrase_flag")?;
+            self.inner.retrieve_passphrase_flag()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_import_leaves_previous_wallet_intact() {
+        let original = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let replacement = "legal winner thank year wave sausage worth useful legal winner thank yellow";
+        let replacement_seed = mnemonic_to_seed_with_passphrase(replacement, "TREZOR").unwrap();
+
+        for fail_on in ["store_mnemonic", "store_seed", "retrieve_passphrase_flag"] {
+            let inner = MockSecureStorage::default();
+            import_mnemonic(original.to_string(), None, tauri::State::from(inner.clone()))
+                .await
+                .unwrap();
+            let original_seed = inner.retrieve_seed().unwrap();
+
+            let storage = FailingStorage { inner: inner.clone(), fail_on };
+            let result = store_imported_wallet(&storage, replacement, &replacement_seed, true);
+            assert!(
+                matches!(result, Err(MnemonicImportError::StorageFailed { .. })),
+                "failing {} should fail the import",
+                fail_on
+            );
+
+            // Seed, mnemonic and flag still all describe the original wallet
+            assert_eq!(inner.retrieve_seed().unwrap(), original_seed, "seed after failing {}", fail_on);
+            assert_eq!(inner.retrieve_mnemonic().unwrap().as_deref(), Some(original), "mnemonic after failing {}", fail_on);
+            assert!(!inner.retrieve_passphrase_flag().unwrap(), "flag after failing {}", fail_on);
+        }
+    }
 
     #[tokio::test]
     async fn test_export_mnemonic_success() {
@@ -222,11 +441,14 @@ mod tests {
                 .to_string();
         let seed = core_crypto::mnemonic_to_seed(&mnemonic).unwrap();
         storage.store_mnemonic(&mnemonic).unwrap(); // Pre-populate storage
-        storage.store_seed(&seed).unwrap(); // Also store seed
+        storage.store_seed(seed.expose_secret()).unwrap(); // Also store seed
 
         let result = export_mnemonic(tauri::State::from(storage)).await;
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), mnemonic);
+        let exported = result.unwrap();
+        assert_eq!(exported.mnemonic, mnemonic);
+        assert!(!exported.passphrase_protected);
+        assert!(exported.warning.is_none());
     }
 
     #[tokio::test]
@@ -242,4 +464,102 @@ mod tests {
     }
 
     // TODO: Add more export error tests (AuthenticationFailed, RetrievalFailed)
+
+    #[tokio::test]
+    async fn test_import_with_passphrase_flags_seed_and_warns_on_export() {
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let storage = MockSecureStorage::default();
+        import_mnemonic(
+            mnemonic.to_string(),
+            Some("TREZOR".to_string()),
+            tauri::State::from(storage.clone()),
+        )
+        .await
+        .unwrap();
+
+        // The seed is the passphrase seed, and the passphrase itself is stored nowhere
+        let expected_seed = core_crypto::mnemonic_to_seed_with_passphrase(mnemonic, "TREZOR").unwrap();
+        assert_eq!(storage.retrieve_seed().unwrap().unwrap(), expected_seed.expose_secret().to_vec());
+        assert_eq!(storage.retrieve_mnemonic().unwrap().unwrap(), mnemonic);
+        assert!(storage.retrieve_passphrase_flag().unwrap());
+
+        let exported = export_mnemonic(tauri::State::from(storage)).await.unwrap();
+        assert_eq!(exported.mnemonic, mnemonic);
+        assert!(exported.passphrase_protected);
+        assert_eq!(exported.warning.as_deref(), Some(PASSPHRASE_EXPORT_WARNING));
+    }
+
+    #[tokio::test]
+    async fn test_import_with_empty_passphrase_is_unprotected() {
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let storage = MockSecureStorage::default();
+        import_mnemonic(mnemonic.to_string(), Some(String::new()), tauri::State::from(storage.clone()))
+            .await
+            .unwrap();
+
+        let expected_seed = core_crypto::mnemonic_to_seed(mnemonic).unwrap();
+        assert_eq!(storage.retrieve_seed().unwrap().unwrap(), expected_seed.expose_secret().to_vec());
+        assert!(!storage.retrieve_passphrase_flag().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_key_exchange_public_key_survives_restore() {
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let storage = MockSecureStorage::default();
+        import_mnemonic(mnemonic.to_string(), None, tauri::State::from(storage.clone()))
+            .await
+            .unwrap();
+        let public_key = get_key_exchange_public_key(None, tauri::State::from(storage.clone()))
+            .await
+            .unwrap();
+        let account_key = get_key_exchange_public_key(Some("account-1".to_string()), tauri::State::from(storage))
+            .await
+            .unwrap();
+        assert_eq!(public_key.len(), 64);
+        assert_ne!(public_key, account_key);
+
+        // A fresh install restored from the same phrase derives the same key
+        let restored = MockSecureStorage::default();
+        import_mnemonic(mnemonic.to_string(), None, tauri::State::from(restored.clone()))
+            .await
+            .unwrap();
+        let restored_key = get_key_exchange_public_key(None, tauri::State::from(restored))
+            .await
+            .unwrap();
+        assert_eq!(restored_key, public_key);
+    }
+
+    #[tokio::test]
+    async fn test_identity_public_key_schemes() {
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let storage = MockSecureStorage::default();
+        import_mnemonic(mnemonic.to_string(), None, tauri::State::from(storage.clone()))
+            .await
+            .unwrap();
+
+        let seed = core_crypto::mnemonic_to_seed(mnemonic).unwrap();
+        let path: Slip10Path = "m/44'/501'/0'".parse().unwrap();
+        let (_, expected) = core_crypto::slip10_derive_keypair(&seed, &path).unwrap();
+        let slip10_key =
+            get_identity_public_key(Some("m/44'/501'/0'".to_string()), tauri::State::from(storage.clone()))
+                .await
+                .unwrap();
+        assert_eq!(slip10_key, hex::encode(expected.as_bytes()));
+
+        let hkdf_key = get_identity_public_key(None, tauri::State::from(storage.clone()))
+            .await
+            .unwrap();
+        assert_ne!(hkdf_key, slip10_key);
+
+        let result = get_identity_public_key(Some("m/44/0".to_string()), tauri::State::from(storage)).await;
+        assert!(matches!(result, Err(WalletKeyError::DerivationFailed(_))));
+    }
+
+    #[tokio::test]
+    async fn test_key_exchange_public_key_not_initialized() {
+        let storage = MockSecureStorage::default(); // Empty storage
+
+        let result = get_key_exchange_public_key(None, tauri::State::from(storage)).await;
+        assert!(matches!(result, Err(WalletKeyError::NotInitialized)));
+    }
 }